@@ -1,22 +1,52 @@
 mod buffers;
 pub mod num_types;
+pub mod number_format;
 
 use crate::log::symbol_type::SymbolType::*;
-use crate::log::Log;
-use buffers::Collapse;
+use crate::log::{
+    calculus, is_identifier_char, is_identifier_start, sequences, special, IntMode, Log,
+};
+pub use buffers::BitwiseOp;
+use buffers::{Collapse, MultiplicationOp};
 use itertools::Itertools;
-use num_types::NumType;
-use std::{clone::Clone, fmt::Display, mem};
+use num_types::{to_integer, Function, NumType};
+use std::{clone::Clone, fmt::Display};
 
-#[derive(Debug, Clone)]
+// Words which act as operators and so can't be used as names
+pub const KEYWORDS: &[&str] = &["xor", "mod", "step"];
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum CalculatorError {
     UnknownSymbol(String),
     ParseNumberErrror,
     MissingFunctionParameters(String),
     InvalidCommand(String),
+    InvalidCommandArgument(String),
     RecursiveVectors,
     ComponentAccessError,
     ComponentDNE,
+    InvalidName(String),
+    ReservedName(String),
+    IntegerRequired(String),
+    IntegerOverflow(String),
+    BeyondExactIntegers(String),
+    DivisionByZero,
+    UnexpectedCharacter(char, usize),
+    UnbalancedBracket(char, usize),
+    UnclosedBracket(char),
+    DomainError(String),
+    EmptyInput(String),
+    ShapeMismatch(usize, usize),
+    CrossNeeds3D,
+    VectorTooLong(usize),
+    FunctionAsNumber,
+    RecursionLimit,
+    ConvergenceFailure(String),
+    NoRoots(String),
+    NoRootsInRange(String, f64, f64),
+    EveryValueRoot(String),
+    EquationUnknowns(usize),
+    ComplexNotSupported,
 }
 
 impl Display for CalculatorError {
@@ -36,6 +66,13 @@ impl Display for CalculatorError {
             CalculatorError::InvalidCommand(s) => {
                 write!(f, "Error: Command \"{}\" not recognized", s)
             }
+            CalculatorError::InvalidCommandArgument(s) => {
+                write!(
+                    f,
+                    "Error: Invalid or missing argument for command \"{}\"",
+                    s
+                )
+            }
             CalculatorError::RecursiveVectors => {
                 write!(f, "Error: Vectors may not contain other vectors")
             }
@@ -45,14 +82,92 @@ impl Display for CalculatorError {
             CalculatorError::ComponentDNE => {
                 write!(f, "Error: Component does not exist")
             }
+            CalculatorError::InvalidName(s) => {
+                write!(f, "Error: \"{}\" is not a valid variable name", s)
+            }
+            CalculatorError::ReservedName(s) => {
+                write!(
+                    f,
+                    "Error: \"{}\" is a built in name and cannot be assigned to",
+                    s
+                )
+            }
+            CalculatorError::IntegerRequired(s) => {
+                write!(f, "Error: \"{}\" only works on whole numbers", s)
+            }
+            CalculatorError::BeyondExactIntegers(s) => write!(
+                f,
+                "Error: \"{}\" only works on whole numbers up to 2^53, past which they can't all be stored",
+                s
+            ),
+            CalculatorError::IntegerOverflow(s) => {
+                write!(f, "Error: Result does not fit in {}", s)
+            }
+            CalculatorError::DivisionByZero => write!(f, "Error: Division by zero"),
+            CalculatorError::UnexpectedCharacter(c, i) => {
+                write!(f, "Error: Unexpected \"{}\" at position {}", c, i)
+            }
+            CalculatorError::UnbalancedBracket(c, i) => {
+                write!(f, "Error: Unmatched \"{}\" at position {}", c, i)
+            }
+            CalculatorError::UnclosedBracket(c) => write!(f, "Error: Unmatched \"{}\"", c),
+            CalculatorError::DomainError(s) => {
+                write!(f, "Error: Input is outside the domain of \"{}\"", s)
+            }
+            CalculatorError::EmptyInput(s) => {
+                write!(f, "Error: \"{}\" needs at least one value", s)
+            }
+            CalculatorError::ShapeMismatch(l, r) => {
+                write!(
+                    f,
+                    "Error: Vectors of length {} and {} cannot be combined",
+                    l, r
+                )
+            }
+            CalculatorError::CrossNeeds3D => write!(f, "Error: \"cross\" needs two 3D vectors"),
+            CalculatorError::VectorTooLong(n) => {
+                write!(f, "Error: Vectors can have at most {} components", n)
+            }
+            CalculatorError::FunctionAsNumber => {
+                write!(
+                    f,
+                    "Error: Functions can only be called, not used as numbers"
+                )
+            }
+            CalculatorError::RecursionLimit => {
+                write!(f, "Error: Too many nested function calls")
+            }
+            CalculatorError::ConvergenceFailure(s) => {
+                write!(f, "Error: \"{}\" could not find an accurate result", s)
+            }
+            CalculatorError::NoRoots(s) => {
+                write!(f, "Error: No real solutions for \"{}\" found", s)
+            }
+            CalculatorError::NoRootsInRange(s, a, b) => write!(
+                f,
+                "Error: No real solutions for \"{}\" found between {} and {}, roots(...) can search elsewhere",
+                s, a, b
+            ),
+            CalculatorError::EveryValueRoot(s) => {
+                write!(f, "Error: Every value of \"{}\" is a solution", s)
+            }
+            CalculatorError::EquationUnknowns(n) => {
+                write!(f, "Error: Equations need exactly one unknown, found {}", n)
+            }
+            CalculatorError::ComplexNotSupported => write!(
+                f,
+                "Error: Complex numbers can only be taken apart with re, im, abs and arg"
+            ),
         }
     }
 }
 
 // Parses an input string to calculate the output
 pub fn calculate(input: &str, log: &Log) -> String {
-    // Removes any whitespace at all from the string and make it lowercase
-    let mut expression = input.replace(' ', "").to_ascii_lowercase();
+    // Whitespace is skipped while parsing, but separates words such as xor
+    let mut expression = input.trim().to_owned();
+    // Where the expression starts in the input, so errors can point at the right character
+    let mut offset = input.chars().take_while(|c| c.is_whitespace()).count();
 
     // Return nothing if given nothing
     if expression.is_empty() {
@@ -73,31 +188,50 @@ pub fn calculate(input: &str, log: &Log) -> String {
             };
 
             // Remove var from expression so as to not confuse parse()
-            expression.drain(..=var_end);
+            let var_name: String = expression.drain(..=var_end).collect();
+            offset += var_name.chars().count();
+            // Show the user the assignment will fail before they press enter
+            if let Err(e) = log.validate_name(var_name[1..var_end].trim()) {
+                return e.to_string();
+            }
         }
         // Handles commands - we won't run them yet though
         '/' => {
             expression.remove(0);
-            let name: String = mem::take(&mut expression);
-            match log.search_command(&name) {
+            let (name, _) = split_command(&expression);
+            match log.search_command(name) {
                 Some(_) => return String::from("Enter to run command..."),
-                None => return CalculatorError::InvalidCommand(name).to_string(),
+                None => return CalculatorError::InvalidCommand(name.to_owned()).to_string(),
             }
         }
         _ => {}
     }
 
+    if let Err(e) = check_characters(&expression, offset) {
+        return e.to_string();
+    }
+
+    // Random numbers are put back afterwards, so the preview doesn't change on every keystroke
+    // and shows the same numbers pressing enter will give
+    let _frozen = log.random.freeze();
+
     // Parse and return output
-    match parse(expression.chars(), log) {
-        Ok(n) => " = ".to_owned() + &n.to_string(),
+    match evaluate_or_solve(&expression, log) {
+        // Programmer mode previews show several bases at once
+        Ok((label, n)) => match log.int_mode {
+            Some(mode) => label + &log.format.format_programmer(&n, mode),
+            None => label + &log.format.format(&n),
+        },
         Err(e) => e.to_string(),
     }
 }
 
 // Like calculate but will actually try to assign the final value to a variable if one is provided
 pub fn calculate_assign(input: &str, log: &mut Log) -> String {
-    // Remove any whitespace at all from the string
-    let mut expression = input.replace(' ', "").to_ascii_lowercase();
+    // Whitespace is skipped while parsing, but separates words such as xor
+    let mut expression = input.trim().to_owned();
+    // Where the expression starts in the input, so errors can point at the right character
+    let mut offset = input.chars().take_while(|c| c.is_whitespace()).count();
 
     // Return nothing if given nothing
     if expression.is_empty() {
@@ -120,182 +254,743 @@ pub fn calculate_assign(input: &str, log: &mut Log) -> String {
             // Remove var from expression so as to not confuse parse()
             expression.remove(0);
             let var_name: String = expression.drain(..var_end).collect();
+            offset += var_name.chars().count() + 2;
+            let var_name = var_name.trim().to_owned();
             expression.remove(0); // remove '=' separately, not part of var name
 
+            if let Err(e) = log.validate_name(&var_name) {
+                return e.to_string();
+            }
+
             Some(var_name)
         }
         // Handles commands - now, we will run them
         '/' => {
             expression.remove(0);
-            let name: String = mem::take(&mut expression);
-            match log.search_command(&name) {
-                Some(f) => return f(log),
-                None => return CalculatorError::InvalidCommand(name).to_string(),
+            let (name, arg) = split_command(&expression);
+            match log.search_command(name) {
+                Some(f) => return f(log, arg),
+                None => return CalculatorError::InvalidCommand(name.to_owned()).to_string(),
             }
         }
         _ => None,
     };
 
-    let result = parse(expression.chars(), log);
+    let result =
+        check_characters(&expression, offset).and_then(|_| evaluate_or_solve(&expression, log));
 
     // tbh not sure this is "correct" but it works
-    if let (Ok(n), Some(s)) = (&result, assigning_to) {
+    if let (Ok((_, n)), Some(s)) = (&result, assigning_to) {
         log.add_var(s, n);
     }
 
     // Parse and return output
     match result {
-        Ok(n) => {
-            log.last_number = n.clone();
-            " = ".to_owned() + &n.to_string()
+        Ok((label, n)) => {
+            let out = label + &log.format.format(&n);
+            log.last_number = n;
+            out
         }
         Err(e) => e.to_string(),
     }
 }
 
+// Where equations are searched for solutions
+const EQUATION_RANGE: (f64, f64) = (-100.0, 100.0);
+
+// Evaluates an expression, or solves it if it is an equation such as x^2 + 3x = 10
+// Also gives the label to show before the result, " = " or " x = " for an equation in x
+fn evaluate_or_solve(expression: &str, log: &Log) -> Result<(String, NumType), CalculatorError> {
+    let Some((lhs, rhs)) = expression.split_once('=') else {
+        return Ok((String::from(" = "), parse(expression.chars(), log)?));
+    };
+
+    let var = find_unknown(expression, log)?;
+    let body = format!("({}) - ({})", lhs, rhs);
+    let mut f = calculus::Expression::new(&body, &var, log, &var)?;
+    let (a, b) = EQUATION_RANGE;
+    let roots = calculus::roots(&mut f, a, b)?;
+    let n = match roots.as_slice() {
+        [] => return Err(CalculatorError::NoRootsInRange(var, a, b)),
+        [root] => NumType::Scalar(*root),
+        _ => NumType::Vector(roots),
+    };
+    Ok((format!(" {} = ", var), n))
+}
+
+// Finds the one name in an equation which isn't a variable or anything built in
+fn find_unknown(expression: &str, log: &Log) -> Result<String, CalculatorError> {
+    let mut unknowns: Vec<String> = Vec::new();
+    let mut input = expression.chars();
+    let mut previous = ' ';
+    while let Some(c) = input.next() {
+        match c {
+            // Skip the digits of hex literals, which could look like names
+            '0' if radix_prefix(&input).is_some() => {
+                input
+                    .take_while_ref(|c| c.is_ascii_alphanumeric())
+                    .for_each(drop);
+            }
+            // Skip the rest of number literals so the exponent of 2e3 isn't taken for a name
+            c if c.is_ascii_digit() => {
+                parse_chars_to_f64(c, &mut input).ok();
+            }
+            c if is_identifier_start(c) => {
+                let name = c.to_string()
+                    + &input
+                        .take_while_ref(|c| is_identifier_char(*c))
+                        .collect::<String>();
+                // Names after '.' are components, v.xy
+                let is_component = previous == '.';
+                if !is_component
+                    && !KEYWORDS.contains(&name.to_ascii_lowercase().as_str())
+                    && log.search_symbol(&name).is_none()
+                    && !unknowns.contains(&name)
+                {
+                    unknowns.push(name);
+                }
+            }
+            _ => {}
+        }
+        previous = c;
+    }
+    match unknowns.len() {
+        1 => Ok(unknowns.remove(0)),
+        n => Err(CalculatorError::EquationUnknowns(n)),
+    }
+}
+
+// Looks for characters the calculator can't use and brackets which don't match up before parsing
+// Positions in errors count from 1 and include the offset of the expression in the input
+fn check_characters(expression: &str, offset: usize) -> Result<(), CalculatorError> {
+    // Open brackets along with their positions
+    let mut brackets: Vec<(char, usize)> = Vec::new();
+    let all: Vec<char> = expression.chars().collect();
+    let mut chars = expression.chars().enumerate();
+    let mut previous = None;
+    let mut equals = false;
+    while let Some((i, c)) = chars.next() {
+        let position = offset + i + 1;
+        match c {
+            // The arrow of a lambda, x -> x^2
+            '>' if previous == Some('-') => {}
+            // An equation has one '=' between its two sides, outside of any brackets
+            '=' => {
+                if equals || !brackets.is_empty() {
+                    return Err(CalculatorError::UnexpectedCharacter(c, position));
+                }
+                equals = true;
+            }
+            '(' | '[' => brackets.push((c, position)),
+            ')' | ']' => {
+                let open = if c == ')' { '(' } else { '[' };
+                if brackets.pop().map(|(b, _)| b) != Some(open) {
+                    return Err(CalculatorError::UnbalancedBracket(c, position));
+                }
+            }
+            // Numbers separated by a space aren't multiplied, 1 2 is more likely a typo than 2
+            c if c.is_ascii_digit() && previous.is_some_and(char::is_whitespace) => {
+                if follows_value(&all[..i]) {
+                    return Err(CalculatorError::UnexpectedCharacter(c, position));
+                }
+            }
+            // Only used doubled, as shifts
+            '<' | '>' => {
+                if chars.next().map(|(_, next)| next) != Some(c) {
+                    return Err(CalculatorError::UnexpectedCharacter(c, position));
+                }
+            }
+            c if c.is_ascii_alphanumeric() || c.is_whitespace() => {}
+            '_' | '+' | '-' | '*' | '/' | '^' | '%' | '&' | '|' | '~' | '.' | ',' | ':' | '\\'
+            | '°' | '!' => {}
+            _ => return Err(CalculatorError::UnexpectedCharacter(c, position)),
+        }
+        previous = Some(c);
+    }
+    // The innermost bracket still open is the one missing its closing bracket
+    match brackets.pop() {
+        Some((c, position)) => Err(CalculatorError::UnbalancedBracket(c, position)),
+        None => Ok(()),
+    }
+}
+
+// Whether the characters before a space end in a number or name, keywords such as mod don't count
+fn follows_value(before: &[char]) -> bool {
+    let end = before
+        .iter()
+        .rposition(|c| !c.is_whitespace())
+        .map_or(0, |i| i + 1);
+    let start = before[..end]
+        .iter()
+        .rposition(|c| !(is_identifier_char(*c) || *c == '.'))
+        .map_or(0, |i| i + 1);
+    let word: String = before[start..end].iter().collect();
+    !word.is_empty() && !KEYWORDS.contains(&word.to_ascii_lowercase().as_str())
+}
+
+// Splits a command (without the '/') into its name and anything after it, which is the argument
+fn split_command(command: &str) -> (&str, &str) {
+    let name_end = command
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(command.len());
+    command.split_at(name_end)
+}
+
 fn parse<T: Iterator<Item = char> + Clone>(
     mut input: T,
     log: &Log,
 ) -> Result<NumType, CalculatorError> {
+    // A lambda takes up the whole expression, its body is only parsed when it is called
+    if let Some((params, body)) = lambda_params(&input) {
+        for param in &params {
+            log.validate_name(param)?;
+        }
+        let body = body.collect::<String>().trim().to_owned();
+        if body.is_empty() {
+            return Err(CalculatorError::UnknownSymbol(String::from("->")));
+        }
+        return Ok(NumType::Function(Function::Lambda(params, body)));
+    }
+
+    // Lenient vector mode lets vectors of different lengths combine like older versions did
+    let lenient = log.lenient_vectors;
     // Exponent buffer
-    let mut e_buffer = buffers::ExponentBuffer::default();
+    let mut e_buffer = buffers::ExponentBuffer::new(lenient);
     // Multiplication/division buffer:
-    let mut m_buffer = buffers::MultiplicationBuffer::default();
+    let mut m_buffer = buffers::MultiplicationBuffer::new(lenient);
     // Addition/subtraction buffer:
-    let mut a_buffer = buffers::AdditionBuffer::default();
+    let mut a_buffer = buffers::AdditionBuffer::new(lenient);
+    // Bitwise buffers, from tightest to loosest: shifts, &, xor, |
+    let mut bit_buffers: [buffers::BitwiseBuffer; 4] =
+        std::array::from_fn(|_| buffers::BitwiseBuffer::new(lenient));
 
     // Tracks whether or not the last thing the parser found was a number or operator
     // Makes (-) work
     let mut previous_number: bool = false;
 
+    // Ranges (start..end step n) bind more loosely than anything else, so their start and end are
+    // everything before the '..' and 'step'
+    let mut range_start: Option<NumType> = None;
+    let mut range_end: Option<NumType> = None;
+
     // Loop through characters
     while let Some(c) = input.next() {
         match c {
             // Check for parantheses, meaning we need to recursively call parse()
-            // Place the result in the multiplication buffer
+            // Place the result in the exponent buffer
             '(' => {
-                e_buffer.push(parse(
-                    input
-                        .by_ref()
-                        .take_while(|c| *c != ')')
-                        .collect::<Vec<char>>()
-                        .iter()
-                        .cloned(),
-                    log,
-                )?);
+                let group = take_group(&mut input).ok_or(CalculatorError::UnclosedBracket(c))?;
+                let n = parse(group.into_iter(), log)?;
+                push_value(
+                    n,
+                    previous_number,
+                    &mut e_buffer,
+                    &mut m_buffer,
+                    log.int_mode,
+                )?;
 
                 previous_number = true; // () expressions are treated as a single number after evaluation
             }
 
             // Check if we are making a vector type, put it into the exponent buffer
             '[' => {
-                e_buffer.push(parse_to_vec(input.by_ref().take_while(|c| *c != ']'), log)?);
+                let group = take_group(&mut input).ok_or(CalculatorError::UnclosedBracket(c))?;
+                // A slice such as v[1:3] takes part of the vector before it
+                if previous_number && group.contains(&':') {
+                    if let Some(len) = e_buffer.get_back().and_then(component_count) {
+                        let indices = parse_slice(&group, len, log)?;
+                        let n = select_components(e_buffer.get_back(), &indices)?;
+                        e_buffer.remove_back();
+                        e_buffer.push(n)?;
+                        continue;
+                    }
+                    return Err(CalculatorError::ComponentAccessError);
+                }
+                let n = parse_to_vec(&group, log)?;
+                push_value(
+                    n,
+                    previous_number,
+                    &mut e_buffer,
+                    &mut m_buffer,
+                    log.int_mode,
+                )?;
                 previous_number = true;
             }
 
+            // '..' makes a range from everything before it to everything after it
+            '.' if input.clone().next() == Some('.') => {
+                input.next();
+                if range_start.is_some() {
+                    return Err(CalculatorError::UnknownSymbol(String::from("..")));
+                }
+                range_start = Some(collapse_all(
+                    &mut e_buffer,
+                    &mut m_buffer,
+                    &mut a_buffer,
+                    &mut bit_buffers,
+                )?);
+                previous_number = false;
+            }
+            c if is_identifier_start(c) && is_keyword(c, &input, "step") => {
+                input.nth(2);
+                if range_start.is_none() || range_end.is_some() {
+                    return Err(CalculatorError::UnknownSymbol(String::from("step")));
+                }
+                range_end = Some(collapse_all(
+                    &mut e_buffer,
+                    &mut m_buffer,
+                    &mut a_buffer,
+                    &mut bit_buffers,
+                )?);
+                previous_number = false;
+            }
+
             // Check for '.' accessing a vector - if no vec, then assume this is a decimal w/o leading 0
             // jank
             '.' => {
                 if previous_number {
-                    if let Some(NumType::Vector(v)) = e_buffer.get_back().cloned() {
-                        // Remove last element, replace it with the component we're accessing
+                    if let Some(len) = e_buffer.get_back().and_then(component_count) {
+                        // Replace the last element with the components we're accessing
+                        let indices = read_components(&mut input, len)?;
+                        let n = select_components(e_buffer.get_back(), &indices)?;
                         e_buffer.remove_back();
-                        // Determine index from characters following '.'
-                        let index: usize = match input.next().unwrap_or('x') {
-                            'x' => 0,
-                            'y' => 1,
-                            'z' => 2,
-                            c => {
-                                let mut s = String::new();
-                                s.push(c);
-                                input
-                                    .take_while_ref(|c| c.is_ascii_digit())
-                                    .for_each(|c| s.push(c));
-                                s.parse()
-                                    .map_err(|_| CalculatorError::ComponentAccessError)?
-                            }
-                        };
-                        e_buffer.push(match v.get(index) {
-                            Some(f) => NumType::Scalar(*f),
-                            None => return Err(CalculatorError::ComponentDNE),
-                        });
+                        e_buffer.push(n)?;
                     } else {
                         return Err(CalculatorError::ComponentAccessError);
                     }
                 } else {
-                    e_buffer.push(NumType::Scalar(parse_chars_to_f64(c, &mut input)?));
+                    let n = NumType::Scalar(parse_chars_to_f64(c, &mut input)?);
+                    push_value(
+                        n,
+                        previous_number,
+                        &mut e_buffer,
+                        &mut m_buffer,
+                        log.int_mode,
+                    )?;
                     previous_number = true;
                 }
             }
 
+            // Check for a hex, binary or octal literal (0x, 0b, 0o followed by a digit in that base)
+            '0' if radix_prefix(&input).is_some() => {
+                let radix = radix_prefix(&input).expect("Checked by match guard");
+                input.next();
+                let n = parse_chars_to_radix(radix, &mut input, log.int_mode)?;
+                push_value(
+                    n,
+                    previous_number,
+                    &mut e_buffer,
+                    &mut m_buffer,
+                    log.int_mode,
+                )?;
+                previous_number = true;
+            }
+
             // Check for a number, put it into the exponent buffer
             '0'..='9' => {
-                e_buffer.push(NumType::Scalar(parse_chars_to_f64(c, &mut input)?));
+                let n = parse_chars_to_number(c, &mut input, log.int_mode)?;
+                push_value(
+                    n,
+                    previous_number,
+                    &mut e_buffer,
+                    &mut m_buffer,
+                    log.int_mode,
+                )?;
                 previous_number = true;
             }
 
+            // '^' raises the last number to the power of the next one
+            '^' => {
+                e_buffer.raising = true;
+                previous_number = false;
+            }
+
             // Check for operators
             '*' => {
-                m_buffer.push(e_buffer.collapse());
+                m_buffer.push(e_buffer.collapse()?)?;
 
                 previous_number = false;
             }
-            // '/' only sets the multiplication buffer to take the reciprocal of the next number
+            // '/' sets the multiplication buffer to divide by the next number, '//' rounds the result down
             '/' => {
-                m_buffer.push(e_buffer.collapse());
-                m_buffer.dividing = true;
+                m_buffer.push(e_buffer.collapse()?)?;
+                m_buffer.op = if input.clone().next() == Some('/') {
+                    input.next();
+                    MultiplicationOp::FloorDivide
+                } else {
+                    MultiplicationOp::Divide
+                };
+
+                previous_number = false;
+            }
+            // '%' and 'mod' take the remainder of floor division
+            '%' => {
+                m_buffer.push(e_buffer.collapse()?)?;
+                m_buffer.op = MultiplicationOp::Modulo;
+
+                previous_number = false;
+            }
+            // mod is only an operator after a value, otherwise it's the function mod(a, b)
+            c if is_identifier_start(c) && previous_number && is_keyword(c, &input, "mod") => {
+                input.nth(1);
+                m_buffer.push(e_buffer.collapse()?)?;
+                m_buffer.op = MultiplicationOp::Modulo;
 
                 previous_number = false;
             }
             // '+' collapses the multiplication buffer into the addition buffer
             '+' => {
-                m_buffer.push(e_buffer.collapse());
-                a_buffer.push(m_buffer.collapse());
+                m_buffer.push(e_buffer.collapse()?)?;
+                a_buffer.push(m_buffer.collapse()?);
                 previous_number = false;
             }
-            // '-' pushes -1 to the multiplication buffer and, if the last part was a number, collapses the multiplication buffer
+            // '-' negates the next number in the multiplication buffer and, if the last part was a number, collapses the multiplication buffer
             '-' => {
                 // Only collapse the multiplication buffer if the last thing was a number
                 // If it was an operator this is unary minus not subtraction
                 if previous_number {
-                    m_buffer.push(e_buffer.collapse());
-                    a_buffer.push(m_buffer.collapse());
+                    m_buffer.push(e_buffer.collapse()?)?;
+                    a_buffer.push(m_buffer.collapse()?);
                     previous_number = false;
                 }
 
-                // Push -1 to the multiplication buffer to reverse the output
-                m_buffer.push(NumType::Scalar(-1.0));
+                if e_buffer.raising {
+                    // A negative exponent (2^-1) only flips the sign of the exponent
+                    e_buffer.negating = !e_buffer.negating;
+                } else {
+                    // Flip the sign of the next number pushed to the multiplication buffer
+                    m_buffer.negating = !m_buffer.negating;
+                }
+            }
+
+            // Bitwise operators collapse everything tighter than them into their own buffer
+            '&' | '|' | '<' | '>' => {
+                let (op, level) = match c {
+                    '<' if input.next() == Some('<') => (BitwiseOp::ShiftLeft, 0),
+                    '>' if input.next() == Some('>') => (BitwiseOp::ShiftRight, 0),
+                    '&' => (BitwiseOp::And, 1),
+                    '|' => (BitwiseOp::Or, 3),
+                    _ => return Err(CalculatorError::UnknownSymbol(c.to_string())),
+                };
+                m_buffer.push(e_buffer.collapse()?)?;
+                a_buffer.push(m_buffer.collapse()?);
+                buffers::push_bitwise(&mut bit_buffers, a_buffer.collapse()?, level)?;
+                bit_buffers[level].op = Some(op);
+                previous_number = false;
+            }
+            // ~ flips the bits of the next number
+            '~' => e_buffer.inverting = !e_buffer.inverting,
+
+            // xor is a word since ^ is already used for exponents
+            c if is_identifier_start(c) && is_keyword(c, &input, "xor") => {
+                input.nth(1);
+                m_buffer.push(e_buffer.collapse()?)?;
+                a_buffer.push(m_buffer.collapse()?);
+                buffers::push_bitwise(&mut bit_buffers, a_buffer.collapse()?, 2)?;
+                bit_buffers[2].op = Some(BitwiseOp::Xor);
+                previous_number = false;
             }
 
             // Else, it must be some sort of symbol
-            // Symbol names are made up of only ascii alphabetic chars
-            // Numbers cannot be used in symbol names or it would not be possible to differentiate symbol then number from one symbol
-            'A'..='Z' | 'a'..='z' => {
+            // Symbol names start with a letter or underscore and may then contain digits as well
+            // A number directly before a symbol (2x) is still fine since symbols cannot start with one
+            c if is_identifier_start(c) => {
                 let name: String = c.to_string()
                     + &(input
-                        .take_while_ref(|c| c.is_ascii_alphabetic())
+                        .take_while_ref(|c| is_identifier_char(*c))
                         .collect::<String>());
-                match log.search_symbol(&name) {
-                    Some(Variable(n)) => e_buffer.push(n.clone()),
-                    Some(DefaultFn(f)) => e_buffer.push(f(get_function_params(&mut input, log)?)?),
-                    Some(UserFn) => {} // TODO: Implement user functions
+                let n = match log.search_symbol(&name) {
+                    // Variables holding functions can be called like built in ones
+                    Some(Variable(NumType::Function(f)))
+                        if next_non_whitespace(&input) == Some('(') =>
+                    {
+                        Callable::new(f, log).call(get_function_params(&mut input, log, &name)?)?
+                    }
+                    Some(Variable(n)) => n.clone(),
+                    // A function name on its own is a value, for passing to functions such as map
+                    Some(DefaultFn(_)) if next_non_whitespace(&input).is_none() => {
+                        NumType::Function(Function::BuiltIn(name))
+                    }
+                    Some(DefaultFn(f)) => f(get_function_params(&mut input, log, &name)?, log)?,
+                    Some(LazyFn(_)) if next_non_whitespace(&input).is_none() => {
+                        NumType::Function(Function::BuiltIn(name))
+                    }
+                    Some(LazyFn(f)) => f(&get_raw_params(&mut input, &name)?, log)?,
+                    Some(UserFn) => continue, // TODO: Implement user functions
                     None => return Err(CalculatorError::UnknownSymbol(name)),
-                }
+                };
+                push_value(
+                    n,
+                    previous_number,
+                    &mut e_buffer,
+                    &mut m_buffer,
+                    log.int_mode,
+                )?;
 
                 previous_number = true;
             }
 
+            // ° marks the last number as being in degrees, converting it to the current angle units
+            '°' => {
+                if let Some(n) = e_buffer.get_back().cloned().and_then(NumType::scalar_value) {
+                    e_buffer.remove_back();
+                    e_buffer.push(NumType::Scalar(log.angle_mode.degrees_to_mode(n)))?;
+                } else {
+                    return Err(CalculatorError::ParseNumberErrror);
+                }
+            }
+
+            // ! takes the factorial of the last number, non-integers go through the gamma function
+            '!' => match e_buffer.get_back().cloned() {
+                Some(n) if previous_number => {
+                    e_buffer.remove_back();
+                    e_buffer.push(n.try_map(special::factorial)?)?;
+                }
+                _ => return Err(CalculatorError::UnknownSymbol(c.to_string())),
+            },
+
             // \ character takes the last answer
-            '\\' => e_buffer.push(log.last_number.clone()),
+            '\\' => {
+                push_value(
+                    log.last_number.clone(),
+                    previous_number,
+                    &mut e_buffer,
+                    &mut m_buffer,
+                    log.int_mode,
+                )?;
+                previous_number = true;
+            }
+
+            // Whitespace only separates things
+            c if c.is_whitespace() => {}
+
+            // Anything else should have been caught by check_characters, but never guess
+            _ => return Err(CalculatorError::UnknownSymbol(c.to_string())),
+        }
+    }
+
+    let mut result = collapse_all(
+        &mut e_buffer,
+        &mut m_buffer,
+        &mut a_buffer,
+        &mut bit_buffers,
+    )?;
+    if let Some(start) = range_start {
+        // Without a step, the last part is the end of the range
+        let (end, step) = match range_end {
+            Some(end) => (end, Some(result)),
+            None => (result, None),
+        };
+        let bound = |n: NumType| {
+            n.scalar_value()
+                .ok_or(CalculatorError::DomainError(String::from("range")))
+        };
+        let step = step.map(bound).transpose()?;
+        result = NumType::Vector(sequences::range(bound(start)?, bound(end)?, step)?);
+    }
+
+    // Programmer mode keeps every result inside its integer type
+    match log.int_mode {
+        Some(mode) => mode.apply(result),
+        None => Ok(result),
+    }
+}
+
+// A function value along with the log to run it in, so it can be called many times (once per element in map)
+pub struct Callable<'a> {
+    function: &'a Function,
+    log: &'a Log,
+    // Lambdas run in a copy of the log with their parameters as variables, made on the first call
+    scope: Option<Log>,
+}
+
+impl<'a> Callable<'a> {
+    pub fn new(function: &'a Function, log: &'a Log) -> Self {
+        Callable {
+            function,
+            log,
+            scope: None,
+        }
+    }
+
+    pub fn call(&mut self, args: Vec<NumType>) -> Result<NumType, CalculatorError> {
+        match self.function {
+            // Lazy functions such as sum also work when given plain values
+            Function::BuiltIn(name) => match self.log.search_default_function(name) {
+                Some(f) => f(args, self.log),
+                None => Err(CalculatorError::UnknownSymbol(name.clone())),
+            },
+            Function::Lambda(params, body) => {
+                if args.len() != params.len() {
+                    return Err(CalculatorError::MissingFunctionParameters(
+                        self.function.to_string(),
+                    ));
+                }
+                if self.scope.is_none() {
+                    self.scope = Some(self.log.scope()?);
+                }
+                let scope = self.scope.as_mut().expect("Scope was just made");
+                for (param, arg) in params.iter().zip(args) {
+                    scope.add_var(param.clone(), &arg);
+                }
+                evaluate(body, scope)
+            }
+        }
+    }
+}
 
-            // otherwise ignore, calc has no clue what to do with this symbol
+// Collapses every buffer into the next, giving the value of everything parsed so far
+fn collapse_all(
+    e_buffer: &mut buffers::ExponentBuffer,
+    m_buffer: &mut buffers::MultiplicationBuffer,
+    a_buffer: &mut buffers::AdditionBuffer,
+    bit_buffers: &mut [buffers::BitwiseBuffer],
+) -> Result<NumType, CalculatorError> {
+    m_buffer.push(e_buffer.collapse()?)?;
+    a_buffer.push(m_buffer.collapse()?);
+    let last = bit_buffers.len() - 1;
+    buffers::push_bitwise(bit_buffers, a_buffer.collapse()?, last)?;
+    bit_buffers[last].collapse()
+}
+
+// Pushes a value into the exponent buffer
+// Two values next to each other without an operator (2x, 3(1 + 1)) are multiplied
+// In programmer mode whole values are used as integers of its type, otherwise as plain numbers
+fn push_value(
+    n: NumType,
+    previous_number: bool,
+    e_buffer: &mut buffers::ExponentBuffer,
+    m_buffer: &mut buffers::MultiplicationBuffer,
+    int_mode: Option<IntMode>,
+) -> Result<(), CalculatorError> {
+    if previous_number && !e_buffer.raising {
+        m_buffer.push(e_buffer.collapse()?)?;
+    }
+    e_buffer.push(match int_mode {
+        Some(mode) => mode.adopt(n),
+        None => n.real(),
+    })
+}
+
+// Takes everything up to the bracket closing the one just read, leaving the iterator after it
+// None if the input ends before the bracket is closed
+fn take_group<T: Iterator<Item = char>>(input: &mut T) -> Option<Vec<char>> {
+    let mut depth = 0;
+    let mut group = Vec::new();
+    for c in input.by_ref() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' if depth == 0 => return Some(group),
+            ')' | ']' => depth -= 1,
             _ => {}
         }
+        group.push(c);
     }
+    None
+}
 
-    m_buffer.push(e_buffer.collapse());
-    a_buffer.push(m_buffer.collapse());
-    Ok(a_buffer.collapse())
+// Splits characters at every comma which isn't inside brackets
+fn split_top_level(chars: &[char]) -> Vec<&[char]> {
+    let mut depth = 0;
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for (i, c) in chars.iter().enumerate() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&chars[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&chars[start..]);
+    parts
+}
+
+// Checks for the start of a lambda, either x -> or (x, y) ->, returning its parameters and an iterator over its body
+fn lambda_params<T: Iterator<Item = char> + Clone>(input: &T) -> Option<(Vec<String>, T)> {
+    let mut ahead = input.clone();
+    ahead.take_while_ref(|c| c.is_whitespace()).for_each(drop);
+    let params = match ahead.next()? {
+        '(' => {
+            let group: String = take_group(&mut ahead)?.into_iter().collect();
+            if group.trim().is_empty() {
+                Vec::new()
+            } else {
+                group.split(',').map(|p| p.trim().to_owned()).collect()
+            }
+        }
+        c if is_identifier_start(c) => vec![
+            c.to_string()
+                + &ahead
+                    .take_while_ref(|c| is_identifier_char(*c))
+                    .collect::<String>(),
+        ],
+        _ => return None,
+    };
+    ahead.take_while_ref(|c| c.is_whitespace()).for_each(drop);
+    match (ahead.next(), ahead.next()) {
+        (Some('-'), Some('>')) => Some((params, ahead)),
+        _ => None,
+    }
+}
+
+fn next_non_whitespace<T: Iterator<Item = char> + Clone>(input: &T) -> Option<char> {
+    input.clone().find(|c| !c.is_whitespace())
+}
+
+// Checks whether the characters starting at c spell out a keyword, which must end at a non-identifier character
+fn is_keyword<T: Iterator<Item = char> + Clone>(c: char, input: &T, keyword: &str) -> bool {
+    let mut word = c.to_string();
+    word.extend(input.clone().take_while(|c| is_identifier_char(*c)));
+    word.eq_ignore_ascii_case(keyword)
+}
+
+// Finds the base of a literal after its leading 0, if the next characters are a prefix and a valid digit
+fn radix_prefix<T: Iterator<Item = char> + Clone>(input: &T) -> Option<u32> {
+    let mut ahead = input.clone();
+    let radix = match ahead.next()? {
+        'x' | 'X' => 16,
+        'b' | 'B' => 2,
+        'o' | 'O' => 8,
+        _ => return None,
+    };
+    ahead.next()?.to_digit(radix).map(|_| radix)
+}
+
+// Turns the digits of a hex/binary/octal literal into a number, an integer in programmer mode
+// Literals are bit patterns, so outside of it ones past 2^53 which a float can't hold exactly are
+// refused rather than rounded
+fn parse_chars_to_radix<T: Iterator<Item = char> + Clone>(
+    radix: u32,
+    iter: &mut T,
+    int_mode: Option<IntMode>,
+) -> Result<NumType, CalculatorError> {
+    let digits: String = iter.take_while_ref(|c| c.is_digit(radix)).collect();
+    match (u64::from_str_radix(&digits, radix), int_mode) {
+        (Ok(n), Some(mode)) => Ok(mode.literal(n)),
+        (Ok(n), None) if n <= num_types::MAX_EXACT_INTEGER => Ok(NumType::Scalar(n as f64)),
+        _ => Err(CalculatorError::ParseNumberErrror),
+    }
+}
+
+// Like parse_chars_to_f64, but whole numbers in programmer mode are read as integers so 64 bit
+// values aren't rounded
+fn parse_chars_to_number<T: Iterator<Item = char> + Clone>(
+    first: char,
+    iter: &mut T,
+    int_mode: Option<IntMode>,
+) -> Result<NumType, CalculatorError> {
+    let s = read_number_chars(first, iter);
+    match (int_mode, s.parse::<u64>()) {
+        (Some(mode), Ok(n)) => Ok(mode.literal(n)),
+        _ => s
+            .parse()
+            .map(NumType::Scalar)
+            .map_err(|_| CalculatorError::ParseNumberErrror),
+    }
 }
 
 // Turns the character iterator into a float or throws an error
@@ -303,77 +998,202 @@ fn parse_chars_to_f64<T: Iterator<Item = char> + Clone>(
     first: char,
     iter: &mut T,
 ) -> Result<f64, CalculatorError> {
-    if let Ok(f) = (first.to_string()
-        + &(iter
-            .take_while_ref(|c| *c == '.' || c.is_ascii_digit())
-            .collect::<String>()))
-        .parse()
-    {
+    if let Ok(f) = read_number_chars(first, iter).parse() {
         Ok(f)
     } else {
         Err(CalculatorError::ParseNumberErrror)
     }
 }
 
-// Parses a character iterator of numbers separated by commas to a NumType::Vector
-// Vectors may contain vectors. This is a feature because it can be so why not
-fn parse_to_vec<T: Iterator<Item = char>>(iter: T, log: &Log) -> Result<NumType, CalculatorError> {
-    let mut v = Vec::<f64>::new();
-    let mut num_string = String::new();
-    for c in iter {
-        if c == ',' {
-            // Parse string to a number and push it into the vector, then clear
-            if let Some(n) = parse(num_string.chars(), log)?.scalar_value() {
-                v.push(n);
-            } else {
-                return Err(CalculatorError::RecursiveVectors);
-            };
-            num_string.clear()
-        } else {
-            // keep adding characters to the number
-            num_string.push(c);
+// Takes the characters of a decimal number, including any exponent
+fn read_number_chars<T: Iterator<Item = char> + Clone>(first: char, iter: &mut T) -> String {
+    let mut s = first.to_string();
+    loop {
+        let mut ahead = iter.clone();
+        match ahead.next() {
+            Some(c) if c.is_ascii_digit() => {}
+            // Two dots start a range rather than a decimal point
+            Some('.') if ahead.next() != Some('.') => {}
+            _ => break,
+        }
+        s.extend(iter.next());
+    }
+    // An exponent like 1e-8, an e without digits after it is Euler's number instead (2e)
+    let mut ahead = iter.clone();
+    if let Some(e @ ('e' | 'E')) = ahead.next() {
+        let sign = ahead.clone().next().filter(|c| *c == '+' || *c == '-');
+        if sign.is_some() {
+            ahead.next();
+        }
+        if ahead.clone().next().is_some_and(|c| c.is_ascii_digit()) {
+            s.push(e);
+            s.extend(sign);
+            s.extend(ahead.take_while_ref(|c| c.is_ascii_digit()));
+            *iter = ahead;
         }
     }
+    s
+}
+
+// Reads the components after '.', either a swizzle (xyzw or rgba, such as .zyx) or an index
+// Negative indices count back from the end of the vector
+fn read_components<T: Iterator<Item = char> + Clone>(
+    input: &mut T,
+    len: usize,
+) -> Result<Vec<usize>, CalculatorError> {
+    let first = input.next().unwrap_or('x');
+    if first.is_ascii_alphabetic() {
+        let names = first.to_string()
+            + &input
+                .take_while_ref(|c| c.is_ascii_alphabetic())
+                .collect::<String>();
+        // Like GLSL, xyzw and rgba can't be mixed in one swizzle
+        let set = if "xyzw".contains(first) {
+            "xyzw"
+        } else {
+            "rgba"
+        };
+        return names
+            .chars()
+            .map(|c| match set.find(c) {
+                Some(i) if i < len => Ok(i),
+                Some(_) => Err(CalculatorError::ComponentDNE),
+                None => Err(CalculatorError::ComponentAccessError),
+            })
+            .collect();
+    }
 
-    // include the last number too
-    if let Some(n) = parse(num_string.chars(), log)?.scalar_value() {
-        v.push(n);
+    let mut s = first.to_string();
+    input
+        .take_while_ref(|c| c.is_ascii_digit())
+        .for_each(|c| s.push(c));
+    let index: i64 = s
+        .parse()
+        .map_err(|_| CalculatorError::ComponentAccessError)?;
+    Ok(vec![resolve_index(index, len)?])
+}
+
+// Turns a possibly negative index into a position in a vector of length len
+fn resolve_index(index: i64, len: usize) -> Result<usize, CalculatorError> {
+    let i = if index < 0 { index + len as i64 } else { index };
+    if (0..len as i64).contains(&i) {
+        Ok(i as usize)
     } else {
-        return Err(CalculatorError::RecursiveVectors);
+        Err(CalculatorError::ComponentDNE)
+    }
+}
+
+// Finds the indices taken by a slice start:end, where either end may be left out
+// Like indices, negative bounds count back from the end
+fn parse_slice(chars: &[char], len: usize, log: &Log) -> Result<Vec<usize>, CalculatorError> {
+    let colon = chars
+        .iter()
+        .position(|c| *c == ':')
+        .expect("Checked by caller");
+    let bound = |part: &[char], default: usize| -> Result<usize, CalculatorError> {
+        if part.iter().all(|c| c.is_whitespace()) {
+            return Ok(default);
+        }
+        let n = parse(part.iter().cloned(), log)?
+            .scalar_value()
+            .ok_or(CalculatorError::ComponentAccessError)?;
+        let i = to_integer(n, "slice")?;
+        // The end of a slice may be one past the last component
+        let i = if i < 0 { i + len as i64 } else { i };
+        if (0..=len as i64).contains(&i) {
+            Ok(i as usize)
+        } else {
+            Err(CalculatorError::ComponentDNE)
+        }
     };
+    let start = bound(&chars[..colon], 0)?;
+    let end = bound(&chars[colon + 1..], len)?;
+    Ok((start..end.max(start)).collect())
+}
+
+// Number of components which can be accessed, vectors of complex numbers have them too
+fn component_count(n: &NumType) -> Option<usize> {
+    match n {
+        NumType::Vector(v) => Some(v.len()),
+        NumType::Complex(v) => Some(v.len()),
+        _ => None,
+    }
+}
+
+// A single real component is a scalar, anything else stays a vector
+// Complex components stay complex, there's no complex scalar
+fn select_components(n: Option<&NumType>, indices: &[usize]) -> Result<NumType, CalculatorError> {
+    match (n, indices) {
+        (Some(NumType::Vector(v)), [i]) => Ok(NumType::Scalar(v[*i])),
+        (Some(NumType::Vector(v)), _) => {
+            Ok(NumType::Vector(indices.iter().map(|i| v[*i]).collect()))
+        }
+        (Some(NumType::Complex(v)), _) => {
+            Ok(NumType::Complex(indices.iter().map(|i| v[*i]).collect()))
+        }
+        _ => Err(CalculatorError::ComponentAccessError),
+    }
+}
+
+// Parses characters of numbers separated by commas to a NumType::Vector
+// Vectors may not contain other vectors
+fn parse_to_vec(chars: &[char], log: &Log) -> Result<NumType, CalculatorError> {
+    // [] is an empty vector rather than [1]
+    if chars.iter().all(|c| c.is_whitespace()) {
+        return Ok(NumType::Vector(Vec::new()));
+    }
+
+    let mut v = Vec::<f64>::new();
+    for part in split_top_level(chars) {
+        // Parse each part to a number and push it into the vector
+        if let Some(n) = parse(part.iter().cloned(), log)?.scalar_value() {
+            v.push(n);
+        } else {
+            return Err(CalculatorError::RecursiveVectors);
+        };
+    }
 
     Ok(NumType::Vector(v))
 }
 
-// TODO: FIX!
+// Reads the bracketed, comma separated parameters after a function name and evaluates each of them
 fn get_function_params<T: Iterator<Item = char> + Clone>(
     iter: &mut T,
     log: &Log,
+    name: &str,
 ) -> Result<Vec<NumType>, CalculatorError> {
-    // Vector we will return when finished
-    let mut v: Vec<NumType> = Vec::new();
-
-    // Collect the entire function input into a string and separate by commas
-    let mut params = iter.take_while(|c| *c != ')');
-    while let Some(c) = params.next() {
-        let mut s = String::from(c);
-        let mut in_vector = false;
-        params
-            .by_ref()
-            .take_while(|c| {
-                if *c == '[' {
-                    in_vector = true;
-                } else if *c == ']' {
-                    in_vector = false;
-                }
-                in_vector || *c != ','
-            })
-            .for_each(|c| s.push(c));
-        v.push(parse(s.chars(), log)?);
+    get_raw_params(iter, name)?
+        .iter()
+        .map(|p| evaluate(p, log))
+        .collect()
+}
+
+// Reads the parameters after a function name as text, for functions which evaluate them themselves
+fn get_raw_params<T: Iterator<Item = char> + Clone>(
+    iter: &mut T,
+    name: &str,
+) -> Result<Vec<String>, CalculatorError> {
+    // Functions must be called with brackets, sin 3 is not allowed
+    iter.take_while_ref(|c| c.is_whitespace()).for_each(drop);
+    if iter.next() != Some('(') {
+        return Err(CalculatorError::MissingFunctionParameters(name.to_owned()));
     }
 
-    // println!("{:?}", v);
-    Ok(v)
+    let params = take_group(iter).ok_or(CalculatorError::UnclosedBracket('('))?;
+    if params.iter().all(|c| c.is_whitespace()) {
+        return Ok(Vec::new());
+    }
+
+    Ok(split_top_level(&params)
+        .into_iter()
+        .map(|p| p.iter().collect())
+        .collect())
+}
+
+// Evaluates an expression on its own, such as a parameter given to a lazy function
+// Functions work on plain numbers, so programmer mode integers are given to them as those
+pub fn evaluate(expression: &str, log: &Log) -> Result<NumType, CalculatorError> {
+    parse(expression.chars(), log).map(NumType::real)
 }
 
 #[cfg(test)]
@@ -1,86 +1,191 @@
 use std::collections::VecDeque;
 
 use super::num_types::NumType;
+use super::CalculatorError;
 use NumType::*;
 
+// Operators with the same precedence as multiplication
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum MultiplicationOp {
+    #[default]
+    Multiply,
+    Divide,
+    FloorDivide,
+    Modulo,
+}
+
 #[derive(Default)]
 pub struct MultiplicationBuffer {
-    numbers: Vec<NumType>,
-    pub dividing: bool,
+    numbers: Vec<(MultiplicationOp, NumType)>,
+    // Operator applied to the next number pushed
+    pub op: MultiplicationOp,
+    // Unary minus flips the sign of the next number without using up the operator
+    pub negating: bool,
+    pub lenient: bool,
 }
 #[derive(Default)]
 pub struct AdditionBuffer {
     numbers: Vec<NumType>,
+    pub lenient: bool,
 }
 
 #[derive(Default)]
 pub struct ExponentBuffer {
     numbers: VecDeque<NumType>,
+    // Set by ^, the next number is an exponent rather than a new value
+    pub raising: bool,
+    // ~ and - in front of the next number
+    pub inverting: bool,
+    pub negating: bool,
+    pub lenient: bool,
+}
+
+// Bitwise operators, all of which bind more loosely than + and -
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BitwiseOp {
+    ShiftLeft,
+    ShiftRight,
+    And,
+    Xor,
+    Or,
+}
+
+// Holds numbers joined by bitwise operators of the same precedence
+#[derive(Default)]
+pub struct BitwiseBuffer {
+    numbers: Vec<(Option<BitwiseOp>, NumType)>,
+    pub op: Option<BitwiseOp>,
+    pub lenient: bool,
 }
 
 pub trait Collapse {
-    fn collapse(&mut self) -> NumType;
+    fn collapse(&mut self) -> Result<NumType, CalculatorError>;
+}
+
+// Every buffer has a lenient flag for the legacy vector mode, where mismatched vectors are made to fit
+// rather than giving an error
+fn fit(n: NumType, lhs: &NumType, lenient: bool) -> NumType {
+    if lenient {
+        n.fit_to(lhs)
+    } else {
+        n
+    }
 }
 
 impl Collapse for ExponentBuffer {
-    fn collapse(&mut self) -> NumType {
+    fn collapse(&mut self) -> Result<NumType, CalculatorError> {
+        self.raising = false;
         match self.numbers.len() {
-            0 => Scalar(1.0),
-            1 => self.numbers.pop_back().unwrap(),
+            0 => Ok(Scalar(1.0)),
+            1 => Ok(self.numbers.pop_back().unwrap()),
             _ => {
                 let mut total = self.numbers.pop_front().unwrap();
-                for n in self.numbers.iter() {
-                    total = total.pow(n);
+                for n in self.numbers.drain(..) {
+                    let n = fit(n, &total, self.lenient);
+                    total = total.pow(&n)?;
                 }
-                self.numbers.clear();
-                total
+                Ok(total)
             }
         }
     }
 }
 
 impl Collapse for MultiplicationBuffer {
-    // multiply everything in the buffer together, clear the buffer, and return the result
-    fn collapse(&mut self) -> NumType {
-        let mut p = Scalar(1.0);
-        for n in &self.numbers {
-            p = p * n;
+    // apply everything in the buffer from left to right, clear the buffer, and return the result
+    fn collapse(&mut self) -> Result<NumType, CalculatorError> {
+        // Operators always come after a number, so the first one is only ever multiplied by 1
+        // Starting from it instead lets a lone function pass through untouched
+        let mut numbers = self.numbers.drain(..);
+        let mut p = match numbers.next() {
+            Some((_, n)) => n,
+            None => return Ok(Scalar(1.0)),
+        };
+        for (op, n) in numbers {
+            let n = fit(n, &p, self.lenient);
+            p = match op {
+                MultiplicationOp::Multiply => (p * n)?,
+                MultiplicationOp::Divide => (p / n)?,
+                MultiplicationOp::FloorDivide => p.floor_div(&n)?,
+                MultiplicationOp::Modulo => p.modulo(&n)?,
+            };
         }
-        self.numbers.clear();
-        p
+        Ok(p)
     }
 }
 
 impl Collapse for AdditionBuffer {
-    // multiply everything in the buffer together, clear the buffer, and return the result
-    fn collapse(&mut self) -> NumType {
-        let mut p = Scalar(0.0);
-        for n in &self.numbers {
-            p = p + n;
+    // add everything in the buffer together, clear the buffer, and return the result
+    fn collapse(&mut self) -> Result<NumType, CalculatorError> {
+        let mut numbers = self.numbers.drain(..);
+        let mut p = numbers.next().unwrap_or(Scalar(0.0));
+        for n in numbers {
+            let n = fit(n, &p, self.lenient);
+            p = (p + n)?;
+        }
+        Ok(p)
+    }
+}
+
+impl Collapse for BitwiseBuffer {
+    // apply each operator from left to right, clear the buffer, and return the result
+    fn collapse(&mut self) -> Result<NumType, CalculatorError> {
+        let mut numbers = self.numbers.drain(..);
+        let mut total = match numbers.next() {
+            Some((_, n)) => n,
+            None => return Ok(Scalar(0.0)),
+        };
+        for (op, n) in numbers {
+            if let Some(op) = op {
+                let n = fit(n, &total, self.lenient);
+                total = total.bitwise(op, &n)?;
+            }
         }
-        self.numbers.clear();
-        p
+        self.op = None;
+        Ok(total)
     }
 }
 
 impl AdditionBuffer {
+    pub fn new(lenient: bool) -> Self {
+        AdditionBuffer {
+            lenient,
+            ..Default::default()
+        }
+    }
     pub fn push(&mut self, n: NumType) {
         self.numbers.push(n);
     }
 }
 impl MultiplicationBuffer {
-    pub fn push(&mut self, n: NumType) {
-        if self.dividing {
-            self.numbers.push(Scalar(1.0) / n);
-            self.dividing = false;
-        } else {
-            self.numbers.push(n);
+    pub fn new(lenient: bool) -> Self {
+        MultiplicationBuffer {
+            lenient,
+            ..Default::default()
         }
     }
+    pub fn push(&mut self, n: NumType) -> Result<(), CalculatorError> {
+        let n = if self.negating { n.negate()? } else { n };
+        self.numbers.push((self.op, n));
+        self.op = MultiplicationOp::Multiply;
+        self.negating = false;
+        Ok(())
+    }
 }
 impl ExponentBuffer {
-    pub fn push(&mut self, n: NumType) {
+    pub fn new(lenient: bool) -> Self {
+        ExponentBuffer {
+            lenient,
+            ..Default::default()
+        }
+    }
+    pub fn push(&mut self, n: NumType) -> Result<(), CalculatorError> {
+        let n = if self.inverting { n.bitwise_not()? } else { n };
+        let n = if self.negating { n.negate()? } else { n };
         self.numbers.push_back(n);
+        self.raising = false;
+        self.inverting = false;
+        self.negating = false;
+        Ok(())
     }
     pub fn get_back(&self) -> Option<&NumType> {
         self.numbers.back()
@@ -89,3 +194,29 @@ impl ExponentBuffer {
         self.numbers.pop_back();
     }
 }
+impl BitwiseBuffer {
+    pub fn new(lenient: bool) -> Self {
+        BitwiseBuffer {
+            lenient,
+            ..Default::default()
+        }
+    }
+    pub fn push(&mut self, n: NumType) {
+        self.numbers.push((self.op.take(), n));
+    }
+}
+
+// Pushes a number into the tightest bitwise buffer, then collapses each buffer into the next
+// until reaching the buffer at index level
+pub fn push_bitwise(
+    buffers: &mut [BitwiseBuffer],
+    mut n: NumType,
+    level: usize,
+) -> Result<(), CalculatorError> {
+    for b in buffers[..level].iter_mut() {
+        b.push(n);
+        n = b.collapse()?;
+    }
+    buffers[level].push(n);
+    Ok(())
+}
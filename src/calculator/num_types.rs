@@ -1,110 +1,83 @@
 use std::{
     fmt::Display,
-    ops::{Add, Div, Mul, Neg},
+    ops::{Add, Div, Mul},
 };
 
+use super::{buffers::BitwiseOp, CalculatorError};
+use crate::log::IntMode;
+
 // enum containing different types of numbers the calculator may handle
 #[derive(Clone, Debug, PartialEq)]
 pub enum NumType {
     Scalar(f64),
     Vector(Vec<f64>),
+    // Functions are values too, so they can be stored in variables and passed to map and friends
+    Function(Function),
+    // Complex results such as polynomial roots, which can be shown or taken apart with re, im, abs and arg
+    // but not calculated with
+    Complex(Vec<Complex>),
+    // Whole numbers in programmer mode, worked out as integers of the type so 64 bit values stay exact
+    Integer(i128, IntMode),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+// Something which can be called, either an anonymous function such as x -> x^2 or a built in function's name
+#[derive(Clone, Debug, PartialEq)]
+pub enum Function {
+    // Parameter names and the expression they are used in
+    Lambda(Vec<String>, String),
+    BuiltIn(String),
 }
 
 use NumType::*;
 
 // implement math for NumType
+// Vectors combine component by component and must have the same length, scalars apply to every component
+// Anything else gives a ShapeMismatch error
 impl Mul for NumType {
-    type Output = NumType;
+    type Output = Result<NumType, CalculatorError>;
     fn mul(self, rhs: Self) -> Self::Output {
-        return match (self, rhs) {
-            (Scalar(l), Scalar(r)) => Scalar(l * r),
-            (Vector(v), Scalar(n)) => Vector(v.iter().map(|f| f * n).collect()),
-            (Scalar(n), Vector(v)) => Vector(v.iter().map(|f| f * n).collect()),
-            // Vector/Vector behavior multiplies each component by the next vector's corresponding component
-            // If there is no corresponding component it uses 1 instead
-            (Vector(v), Vector(v2)) => {
-                let mut i = v2.iter();
-                Vector(v.iter().map(|f| f * i.next().unwrap_or(&1.0)).collect())
-            }
-        };
+        self * &rhs
     }
 }
 
 impl Mul<&NumType> for NumType {
-    type Output = NumType;
+    type Output = Result<NumType, CalculatorError>;
     fn mul(self, rhs: &Self) -> Self::Output {
-        return match (self, rhs) {
-            (Scalar(l), Scalar(r)) => Scalar(l * r),
-            (Vector(v), Scalar(n)) => Vector(v.iter().map(|f| f * n).collect()),
-            (Scalar(n), Vector(v)) => Vector(v.iter().map(|f| f * n).collect()),
-            // Vector/Vector behavior multiplies each component by the next vector's corresponding component
-            // If there is no corresponding component it uses 1 instead
-            (Vector(v), Vector(v2)) => {
-                let mut i = v2.iter();
-                Vector(v.iter().map(|f| f * i.next().unwrap_or(&1.0)).collect())
-            }
-        };
+        self.zip_with_integers(rhs, |mode, l, r| mode.multiply(l, r), |l, r| Ok(l * r))
     }
 }
 
 impl Div for NumType {
-    type Output = NumType;
+    type Output = Result<NumType, CalculatorError>;
     fn div(self, rhs: Self) -> Self::Output {
-        return match (self, rhs) {
-            (Scalar(l), Scalar(r)) => Scalar(l / r),
-            (Vector(v), Scalar(n)) => Vector(v.iter().map(|f| f / n).collect()),
-            (Scalar(n), Vector(v)) => Vector(v.iter().map(|f| f / n).collect()),
-            // Vector/Vector behavior divides each component by the next vector's corresponding component
-            // If there is no corresponding component it uses 1 instead
-            (Vector(v), Vector(v2)) => {
-                let mut i = v2.iter();
-                Vector(v.iter().map(|f| f / i.next().unwrap_or(&1.0)).collect())
-            }
-        };
+        self / &rhs
+    }
+}
+
+impl Div<&NumType> for NumType {
+    type Output = Result<NumType, CalculatorError>;
+    fn div(self, rhs: &Self) -> Self::Output {
+        self.zip_with_integers(rhs, |mode, l, r| mode.divide(l, r), |l, r| Ok(l / r))
     }
 }
 
 impl Add for NumType {
-    type Output = NumType;
+    type Output = Result<NumType, CalculatorError>;
     fn add(self, rhs: Self) -> Self::Output {
-        return match (self, rhs) {
-            (Scalar(l), Scalar(r)) => Scalar(l + r),
-            (Vector(v), Scalar(n)) => Vector(v.iter().map(|f| f + n).collect()),
-            (Scalar(n), Vector(v)) => Vector(v.iter().map(|f| f + n).collect()),
-            // Vector/Vector behavior adds each component by the next vector's corresponding component
-            // If there is no corresponding component it uses 1 instead
-            (Vector(v), Vector(v2)) => {
-                let mut i = v2.iter();
-                Vector(v.iter().map(|f| f + i.next().unwrap_or(&1.0)).collect())
-            }
-        };
+        self + &rhs
     }
 }
 
 impl Add<&NumType> for NumType {
-    type Output = NumType;
+    type Output = Result<NumType, CalculatorError>;
     fn add(self, rhs: &Self) -> Self::Output {
-        return match (self, rhs) {
-            (Scalar(l), Scalar(r)) => Scalar(l + r),
-            (Vector(v), Scalar(n)) => Vector(v.iter().map(|f| f + n).collect()),
-            (Scalar(n), Vector(v)) => Vector(v.iter().map(|f| f + n).collect()),
-            // Vector/Vector behavior divides each component by the next vector's corresponding component
-            // If there is no corresponding component it uses 1 instead
-            (Vector(v), Vector(v2)) => {
-                let mut i = v2.iter();
-                Vector(v.iter().map(|f| f + i.next().unwrap_or(&1.0)).collect())
-            }
-        };
-    }
-}
-
-impl Neg for NumType {
-    type Output = NumType;
-    fn neg(self) -> Self::Output {
-        match self {
-            Scalar(s) => Scalar(-s),
-            Vector(v) => Vector(v.iter().map(|f| -f).collect()),
-        }
+        self.zip_with_integers(rhs, |mode, l, r| mode.add(l, r), |l, r| Ok(l + r))
     }
 }
 
@@ -123,34 +96,339 @@ impl Display for NumType {
                         .join(", ")
                 )
             }
+            Function(func) => write!(f, "{}", func),
+            Integer(n, _) => write!(f, "{}", n),
+            Complex(v) => write!(
+                f,
+                "[{}]",
+                v.iter()
+                    .map(|z| z.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+impl Display for Complex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.format(|n| n.to_string()))
+    }
+}
+
+impl Complex {
+    pub fn new(re: f64, im: f64) -> Self {
+        Complex { re, im }
+    }
+
+    // Written as a + bi, or just a or bi if the other part is 0
+    pub fn format(&self, format_f64: impl Fn(f64) -> String) -> String {
+        match (self.re, self.im) {
+            (re, 0.0) => format_f64(re),
+            (0.0, im) => format_f64(im) + "i",
+            (re, im) if im < 0.0 => format!("{} - {}i", format_f64(re), format_f64(-im)),
+            (re, im) => format!("{} + {}i", format_f64(re), format_f64(im)),
+        }
+    }
+
+    pub fn abs(self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    pub fn arg(self) -> f64 {
+        self.im.atan2(self.re)
+    }
+
+    pub fn conj(self) -> Self {
+        Complex::new(self.re, -self.im)
+    }
+
+    // e^(i theta), the point on the unit circle at that angle
+    pub fn from_angle(theta: f64) -> Self {
+        Complex::new(theta.cos(), theta.sin())
+    }
+}
+
+impl std::ops::Add for Complex {
+    type Output = Complex;
+    fn add(self, rhs: Complex) -> Complex {
+        Complex::new(self.re + rhs.re, self.im + rhs.im)
+    }
+}
+
+impl std::ops::Sub for Complex {
+    type Output = Complex;
+    fn sub(self, rhs: Complex) -> Complex {
+        Complex::new(self.re - rhs.re, self.im - rhs.im)
+    }
+}
+
+impl std::ops::Mul for Complex {
+    type Output = Complex;
+    fn mul(self, rhs: Complex) -> Complex {
+        Complex::new(
+            self.re * rhs.re - self.im * rhs.im,
+            self.re * rhs.im + self.im * rhs.re,
+        )
+    }
+}
+
+impl std::ops::Div for Complex {
+    type Output = Complex;
+    fn div(self, rhs: Complex) -> Complex {
+        let d = rhs.re * rhs.re + rhs.im * rhs.im;
+        Complex::new(
+            (self.re * rhs.re + self.im * rhs.im) / d,
+            (self.im * rhs.re - self.re * rhs.im) / d,
+        )
+    }
+}
+
+impl Display for Function {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Function::Lambda(params, body) => match params.as_slice() {
+                [param] => write!(f, "{} -> {}", param, body),
+                _ => write!(f, "({}) -> {}", params.join(", "), body),
+            },
+            Function::BuiltIn(name) => write!(f, "{}", name),
         }
     }
 }
 
 // Exponents and more
 impl NumType {
-    pub fn pow(self, rhs: &NumType) -> NumType {
-        match (self, rhs) {
-            (Scalar(l), Scalar(r)) => Scalar(l.powf(*r)),
-            (Vector(v), Scalar(n)) => Vector(v.iter().map(|f| f.powf(*n)).collect()),
-            (Scalar(n), Vector(v)) => Vector(v.iter().map(|f| f.powf(n)).collect()),
-            // Vector/Vector behavior divides each component by the next vector's corresponding component
-            // If there is no corresponding component it uses 1 instead
+    pub fn pow(self, rhs: &NumType) -> Result<NumType, CalculatorError> {
+        self.zip_with_integers(rhs, |mode, l, r| mode.pow(l, r), |l, r| Ok(l.powf(r)))
+    }
+
+    pub fn scalar_value(self) -> Option<f64> {
+        match self.real() {
+            Scalar(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    // Programmer mode integers as plain numbers, for everything which works on f64
+    pub fn real(self) -> NumType {
+        match self {
+            Integer(n, _) => Scalar(n as f64),
+            other => other,
+        }
+    }
+
+    // The value as an integer, if it is one or is a whole number which can be used as one
+    fn integer_value(&self) -> Option<i128> {
+        match self {
+            Integer(n, _) => Some(*n),
+            Scalar(s) if s.fract() == 0.0 && s.abs() < u64::MAX as f64 => Some(*s as i128),
+            _ => None,
+        }
+    }
+
+    pub fn negate(self) -> Result<NumType, CalculatorError> {
+        match self {
+            Integer(n, mode) => Ok(Integer(mode.negate(n)?, mode)),
+            other => other.try_map(|f| Ok(-f)),
+        }
+    }
+
+    // Applies a fallible function to the scalar or to every component of the vector
+    pub fn try_map(
+        self,
+        mut f: impl FnMut(f64) -> Result<f64, CalculatorError>,
+    ) -> Result<NumType, CalculatorError> {
+        match self.real() {
+            Scalar(s) => Ok(Scalar(f(s)?)),
+            Vector(v) => Ok(Vector(v.into_iter().map(f).collect::<Result<_, _>>()?)),
+            other => Err(other.not_real()),
+        }
+    }
+
+    // The error for using something which isn't made of real numbers where they are needed
+    pub fn not_real(&self) -> CalculatorError {
+        match self {
+            Function(_) => CalculatorError::FunctionAsNumber,
+            _ => CalculatorError::ComplexNotSupported,
+        }
+    }
+
+    // Operators on programmer mode integers are worked out in the integer type when the other side
+    // is whole as well, anything else goes through f64
+    fn zip_with_integers(
+        self,
+        rhs: &NumType,
+        int_f: impl Fn(IntMode, i128, i128) -> Result<i128, CalculatorError>,
+        f: impl Fn(f64, f64) -> Result<f64, CalculatorError>,
+    ) -> Result<NumType, CalculatorError> {
+        let mode = match (&self, rhs) {
+            (Integer(_, mode), _) | (_, Integer(_, mode)) => Some(*mode),
+            _ => None,
+        };
+        match (mode, self.integer_value(), rhs.integer_value()) {
+            (Some(mode), Some(l), Some(r)) => Ok(Integer(int_f(mode, l, r)?, mode)),
+            _ => self.zip_with(rhs, f),
+        }
+    }
+
+    // Applies a fallible operation between each pair of components
+    fn zip_with(
+        self,
+        rhs: &NumType,
+        f: impl Fn(f64, f64) -> Result<f64, CalculatorError>,
+    ) -> Result<NumType, CalculatorError> {
+        let real_rhs;
+        let rhs = match rhs {
+            Integer(..) => {
+                real_rhs = rhs.clone().real();
+                &real_rhs
+            }
+            _ => rhs,
+        };
+        Ok(match (self.real(), rhs) {
+            (Scalar(l), Scalar(r)) => Scalar(f(l, *r)?),
+            (Vector(v), Scalar(n)) => {
+                Vector(v.iter().map(|l| f(*l, *n)).collect::<Result<_, _>>()?)
+            }
+            (Scalar(n), Vector(v)) => Vector(v.iter().map(|r| f(n, *r)).collect::<Result<_, _>>()?),
             (Vector(v), Vector(v2)) => {
-                let mut i = v2.iter();
+                if v.len() != v2.len() {
+                    return Err(CalculatorError::ShapeMismatch(v.len(), v2.len()));
+                }
                 Vector(
                     v.iter()
-                        .map(|f| f.powf(*i.next().unwrap_or(&1.0)))
-                        .collect(),
+                        .zip(v2)
+                        .map(|(l, r)| f(*l, *r))
+                        .collect::<Result<_, _>>()?,
                 )
             }
+            (l @ (Function(_) | Complex(_) | Integer(..)), _) => return Err(l.not_real()),
+            (_, r) => return Err(r.not_real()),
+        })
+    }
+
+    // Legacy lenient vector behavior, the right vector is cut short or padded with 1s to fit the left vector
+    pub fn fit_to(self, lhs: &NumType) -> NumType {
+        match (lhs, self) {
+            (Vector(l), Vector(mut r)) => {
+                r.resize(l.len(), 1.0);
+                Vector(r)
+            }
+            (_, rhs) => rhs,
         }
     }
 
-    pub fn scalar_value(self) -> Option<f64> {
+    // Division rounded down, so that a == (a // b) * b + a % b
+    pub fn floor_div(self, rhs: &NumType) -> Result<NumType, CalculatorError> {
+        self.zip_with_integers(
+            rhs,
+            |mode, l, r| mode.floor_divide(l, r),
+            |l, r| {
+                if r == 0.0 {
+                    Err(CalculatorError::DivisionByZero)
+                } else {
+                    Ok((l / r).floor())
+                }
+            },
+        )
+    }
+
+    // Remainder of floor division, which has the same sign as the divisor
+    pub fn modulo(self, rhs: &NumType) -> Result<NumType, CalculatorError> {
+        self.zip_with_integers(
+            rhs,
+            |mode, l, r| mode.modulo(l, r),
+            |l, r| {
+                if r == 0.0 {
+                    Err(CalculatorError::DivisionByZero)
+                } else {
+                    let m = l % r;
+                    if m != 0.0 && (m < 0.0) != (r < 0.0) {
+                        Ok(m + r)
+                    } else {
+                        Ok(m)
+                    }
+                }
+            },
+        )
+    }
+
+    // Remainder of truncated division, which has the same sign as the dividend
+    pub fn rem(self, rhs: &NumType) -> Result<NumType, CalculatorError> {
+        self.zip_with(rhs, |l, r| {
+            if r == 0.0 {
+                Err(CalculatorError::DivisionByZero)
+            } else {
+                Ok(l % r)
+            }
+        })
+    }
+
+    // Euclidean remainder, which is never negative
+    pub fn rem_euclid(self, rhs: &NumType) -> Result<NumType, CalculatorError> {
+        self.zip_with(rhs, |l, r| {
+            if r == 0.0 {
+                Err(CalculatorError::DivisionByZero)
+            } else {
+                Ok(l.rem_euclid(r))
+            }
+        })
+    }
+
+    // Bitwise operators work on whole numbers only, as 64 bit two's complement integers
+    pub fn bitwise(self, op: BitwiseOp, rhs: &NumType) -> Result<NumType, CalculatorError> {
+        let apply = |l: f64, r: f64| -> Result<f64, CalculatorError> {
+            let (l, r) = (
+                to_integer(l, "bitwise operator")?,
+                to_integer(r, "bitwise operator")?,
+            );
+            let result = match op {
+                BitwiseOp::And => l & r,
+                BitwiseOp::Or => l | r,
+                BitwiseOp::Xor => l ^ r,
+                BitwiseOp::ShiftLeft | BitwiseOp::ShiftRight => {
+                    let shift = u32::try_from(r)
+                        .ok()
+                        .filter(|r| *r < i64::BITS)
+                        .ok_or(CalculatorError::IntegerOverflow(String::from("shift")))?;
+                    if op == BitwiseOp::ShiftLeft {
+                        l << shift
+                    } else {
+                        l >> shift
+                    }
+                }
+            };
+            Ok(result as f64)
+        };
+
+        self.zip_with_integers(rhs, |mode, l, r| mode.bitwise(op, l, r), apply)
+    }
+
+    pub fn bitwise_not(self) -> Result<NumType, CalculatorError> {
         match self {
-            Scalar(s) => Some(s),
-            Vector(_) => None,
+            Integer(n, mode) => Ok(Integer(mode.not(n), mode)),
+            other => other.try_map(|f| Ok(!to_integer(f, "~")? as f64)),
         }
     }
 }
+
+// Past 2^53 an f64 can't hold every whole number, so the next one up may be rounded away
+pub const MAX_EXACT_INTEGER: u64 = 1 << f64::MANTISSA_DIGITS;
+
+// Like to_integer, for functions which are only right when every whole number around them is exact
+pub fn to_exact_integer(f: f64, name: &str) -> Result<i64, CalculatorError> {
+    if f.abs() > MAX_EXACT_INTEGER as f64 {
+        Err(CalculatorError::BeyondExactIntegers(name.to_owned()))
+    } else {
+        to_integer(f, name)
+    }
+}
+
+// Converts a float which should be a whole number to an integer, or errors with the name of what needed it
+pub fn to_integer(f: f64, name: &str) -> Result<i64, CalculatorError> {
+    if f.fract() == 0.0 && f >= i64::MIN as f64 && f < i64::MAX as f64 {
+        Ok(f as i64)
+    } else {
+        Err(CalculatorError::IntegerRequired(name.to_owned()))
+    }
+}
@@ -0,0 +1,232 @@
+use std::fmt::Display;
+
+use super::num_types::{NumType, MAX_EXACT_INTEGER};
+use crate::log::IntMode;
+
+// How numbers are written out, the number is the digit count each notation uses
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Notation {
+    // Shortest representation after rounding away float noise, scientific only for very big/small numbers
+    #[default]
+    Smart,
+    // Digits after the decimal point
+    Fixed(usize),
+    // Significant figures
+    Significant(usize),
+    // Digits after the decimal point of the mantissa
+    Scientific(usize),
+    Engineering(usize),
+}
+
+use Notation::*;
+
+// Settings for turning calculator results into text
+#[derive(Clone, Debug, PartialEq)]
+pub struct NumberFormat {
+    pub notation: Notation,
+    pub group_thousands: bool,
+    pub separator: char,
+    // Whole numbers are shown in this base, anything else is always decimal
+    pub base: u32,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        NumberFormat {
+            notation: Notation::default(),
+            group_thousands: false,
+            separator: ',',
+            base: 10,
+        }
+    }
+}
+
+// Smart mode keeps 15 significant figures, every one of which an f64 holds exactly, dropping the noise
+const SMART_DIGITS: usize = 15;
+// Exponents outside of this range are written in scientific notation unless a fixed notation is used
+const PLAIN_EXPONENTS: std::ops::Range<i32> = -7..15;
+
+impl NumberFormat {
+    pub fn format(&self, n: &NumType) -> String {
+        match n {
+            NumType::Scalar(s) => self.format_f64(*s),
+            NumType::Integer(i, _) => self.format_integer(*i),
+            NumType::Vector(v) => format!(
+                "[{}]",
+                v.iter()
+                    .map(|f| self.format_f64(*f))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            NumType::Function(f) => f.to_string(),
+            NumType::Complex(v) => format!(
+                "[{}]",
+                v.iter()
+                    .map(|z| z.format(|n| self.format_f64(n)))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+        }
+    }
+
+    // Programmer mode integers are exact, so they are always written out in full
+    fn format_integer(&self, n: i128) -> String {
+        if self.base == 10 {
+            return self.group(n.to_string());
+        }
+        let sign = if n < 0 { "-" } else { "" };
+        sign.to_owned() + &format_radix(n.unsigned_abs() as u64, self.base)
+    }
+
+    pub fn format_f64(&self, n: f64) -> String {
+        // inf and NaN are left to rust, there's nothing to round
+        if !n.is_finite() {
+            return n.to_string();
+        }
+
+        if self.base != 10 && n.fract() == 0.0 && n.abs() < u64::MAX as f64 {
+            let sign = if n < 0.0 { "-" } else { "" };
+            return sign.to_owned() + &format_radix(n.abs() as u64, self.base);
+        }
+
+        match self.notation {
+            // Whole numbers an f64 holds exactly have no noise to drop, so every digit is shown
+            Smart if n.fract() == 0.0 && n.abs() <= MAX_EXACT_INTEGER as f64 => {
+                self.group(n.to_string())
+            }
+            Smart => {
+                let rounded = round_significant(n, SMART_DIGITS);
+                if n == 0.0 || PLAIN_EXPONENTS.contains(&exponent(rounded)) {
+                    self.group(rounded.to_string())
+                } else {
+                    format!("{:e}", rounded)
+                }
+            }
+            Fixed(d) => self.group(format!("{:.*}", d, n)),
+            Significant(d) => {
+                let d = d.max(1);
+                let rounded = round_significant(n, d);
+                let exp = exponent(rounded);
+                if n == 0.0 || PLAIN_EXPONENTS.contains(&exp) {
+                    let decimals = (d as i32 - 1 - exp).max(0) as usize;
+                    self.group(format!("{:.*}", decimals, rounded))
+                } else {
+                    format!("{:.*e}", d - 1, n)
+                }
+            }
+            Scientific(d) => format!("{:.*e}", d, n),
+            Engineering(d) => {
+                if n == 0.0 {
+                    return format!("{:.*}e0", d, n);
+                }
+                let mut exp = exponent(n) - exponent(n).rem_euclid(3);
+                let mut mantissa = format!("{:.*}", d, n / 10f64.powi(exp));
+                // Rounding the mantissa may carry it up to 1000, which belongs to the next exponent
+                if mantissa.parse::<f64>().is_ok_and(|m| m.abs() >= 1000.0) {
+                    exp += 3;
+                    mantissa = format!("{:.*}", d, n / 10f64.powi(exp));
+                }
+                format!("{}e{}", mantissa, exp)
+            }
+        }
+    }
+
+    // Programmer mode shows whole numbers in hex, decimal and binary at once
+    // Hex and binary show the bits of the integer type, so negative numbers are in two's complement
+    pub fn format_programmer(&self, n: &NumType, int_mode: IntMode) -> String {
+        match n {
+            NumType::Integer(i, _) => format!(
+                "{} | {} | {}",
+                format_radix(int_mode.to_bits(*i), 16),
+                self.group(i.to_string()),
+                format_radix(int_mode.to_bits(*i), 2)
+            ),
+            _ => self.format(n),
+        }
+    }
+
+    // Adds separators to the integer part of a plain decimal number
+    fn group(&self, s: String) -> String {
+        if !self.group_thousands {
+            return s;
+        }
+
+        let (sign, unsigned) = match s.strip_prefix('-') {
+            Some(rest) => ("-", rest),
+            None => ("", s.as_str()),
+        };
+        let (int_part, frac_part) = match unsigned.find('.') {
+            Some(i) => unsigned.split_at(i),
+            None => (unsigned, ""),
+        };
+
+        let mut grouped = String::new();
+        for (i, c) in int_part.chars().enumerate() {
+            if i > 0 && (int_part.len() - i) % 3 == 0 {
+                grouped.push(self.separator);
+            }
+            grouped.push(c);
+        }
+
+        sign.to_owned() + &grouped + frac_part
+    }
+}
+
+impl Notation {
+    // Whether the digit count is one the notation can use. An f64 only has 17 significant figures,
+    // so anything past that would be padding, and very long strings slow down the live preview
+    pub fn digits_in_range(self) -> bool {
+        match self {
+            Smart => true,
+            Fixed(d) => d <= 20,
+            Significant(d) => (1..=17).contains(&d),
+            Scientific(d) | Engineering(d) => d <= 16,
+        }
+    }
+}
+
+impl Display for Notation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Smart => write!(f, "smart notation"),
+            Fixed(d) => write!(f, "fixed notation with {} decimals", d),
+            Significant(d) => write!(f, "{} significant figures", d),
+            Scientific(d) => write!(f, "scientific notation with {} decimals", d),
+            Engineering(d) => write!(f, "engineering notation with {} decimals", d),
+        }
+    }
+}
+
+// Power of ten of the leading digit
+fn exponent(n: f64) -> i32 {
+    if n == 0.0 {
+        0
+    } else {
+        n.abs().log10().floor() as i32
+    }
+}
+
+// Round through rust's scientific formatting, which rounds correctly in decimal
+fn round_significant(n: f64, digits: usize) -> f64 {
+    format!("{:.*e}", digits.max(1) - 1, n).parse().unwrap_or(n)
+}
+
+// Writes a whole number in any base from 2 to 36, with a prefix for the ones which can be typed back in
+pub fn format_radix(mut n: u64, base: u32) -> String {
+    let mut digits = Vec::new();
+    loop {
+        digits.push(std::char::from_digit((n % base as u64) as u32, base).unwrap_or('?'));
+        n /= base as u64;
+        if n == 0 {
+            break;
+        }
+    }
+    let digits: String = digits.iter().rev().collect();
+
+    match base {
+        2 => "0b".to_owned() + &digits,
+        8 => "0o".to_owned() + &digits,
+        16 => "0x".to_owned() + &digits,
+        _ => format!("{} (base {})", digits, base),
+    }
+}
@@ -60,7 +60,7 @@ fn variable() {
 fn consts() {
     let log = Log::new();
     let result = calculate("PI", &log);
-    assert_eq!(result, " = ".to_string() + &PI.to_string());
+    assert_eq!(result, " = 3.14159265358979");
 }
 
 #[test]
@@ -80,26 +80,31 @@ fn exponents_order() {
 #[test]
 fn default_funcs() {
     let log = Log::new();
-    // Not terminating parantheses after a function should just cause it to accept everything
     // should be 1 + (2 * 1) = 3
-    let result = calculate("sin(PI/2) + abs(-2) * sin(PI/2", &log);
+    let result = calculate("sin(PI/2) + abs(-2) * sin(PI/2)", &log);
     assert_eq!(result, " = 3")
 }
 
 #[test]
 fn multiple_fn_args() {
     let log = Log::new();
-    let result = calculate("log(10, 5+5", &log);
+    let result = calculate("log(10, 5+5)", &log);
     assert_eq!(result, " = 1")
 }
 
 #[test]
 fn vector_math() {
-    assert_eq!(Vector(vec![1.0, 1.0]) * Scalar(2.0), Vector(vec![2.0, 2.0]));
-    assert_eq!(Vector(vec![1.0, 1.0]) + Scalar(2.0), Vector(vec![3.0, 3.0]));
+    assert_eq!(
+        Vector(vec![1.0, 1.0]) * Scalar(2.0),
+        Ok(Vector(vec![2.0, 2.0]))
+    );
+    assert_eq!(
+        Vector(vec![1.0, 1.0]) + Scalar(2.0),
+        Ok(Vector(vec![3.0, 3.0]))
+    );
     assert_eq!(
         Vector(vec![1.0, 1.0]) * Vector(vec![1.0, 2.0]),
-        Vector(vec![1.0, 2.0])
+        Ok(Vector(vec![1.0, 2.0]))
     );
 }
 
@@ -128,9 +133,1497 @@ fn component_access() {
     );
 }
 
+#[test]
+fn swizzles() {
+    let mut log = Log::new();
+    calculate_assign("#v = [1, 2, 3, 4]", &mut log);
+    assert_eq!(calculate("v.xy", &log), " = [1, 2]");
+    assert_eq!(calculate("v.zyx", &log), " = [3, 2, 1]");
+    assert_eq!(calculate("v.w", &log), " = 4");
+    assert_eq!(calculate("v.rgba", &log), " = [1, 2, 3, 4]");
+    assert_eq!(calculate("v.bgr", &log), " = [3, 2, 1]");
+    assert_eq!(calculate("v.xx + 1", &log), " = [2, 2]");
+    assert_eq!(calculate("v.-1", &log), " = 4");
+    assert_eq!(calculate("v.-4", &log), " = 1");
+    assert_eq!(
+        calculate("v.-5", &log),
+        CalculatorError::ComponentDNE.to_string()
+    );
+    assert_eq!(
+        calculate("[1, 2].z", &log),
+        CalculatorError::ComponentDNE.to_string()
+    );
+    // xyzw and rgba can't be mixed
+    assert_eq!(
+        calculate("v.xg", &log),
+        CalculatorError::ComponentAccessError.to_string()
+    );
+}
+
+#[test]
+fn slices() {
+    let mut log = Log::new();
+    calculate_assign("#v = [1, 2, 3, 4, 5]", &mut log);
+    assert_eq!(calculate("v[1:3]", &log), " = [2, 3]");
+    assert_eq!(calculate("v[:2]", &log), " = [1, 2]");
+    assert_eq!(calculate("v[3:]", &log), " = [4, 5]");
+    assert_eq!(calculate("v[-2:]", &log), " = [4, 5]");
+    assert_eq!(calculate("v[1:-1]", &log), " = [2, 3, 4]");
+    assert_eq!(calculate("v[:]", &log), " = [1, 2, 3, 4, 5]");
+    assert_eq!(calculate("v[2:3]", &log), " = 3");
+    assert_eq!(calculate("v[3:1]", &log), " = []");
+    assert_eq!(calculate("[1, 2, 3][1 + 1:]", &log), " = 3");
+    assert_eq!(calculate("2v[0:2]", &log), " = [2, 4]");
+    assert_eq!(
+        calculate("v[0:6]", &log),
+        CalculatorError::ComponentDNE.to_string()
+    );
+    assert_eq!(
+        calculate("3[0:1]", &log),
+        CalculatorError::ComponentAccessError.to_string()
+    );
+}
+
 #[test]
 fn last_number() {
     let mut log = Log::default();
     assert_eq!(calculate_assign("1 + 1", &mut log), " = 2");
     assert_eq!(calculate_assign("\\ + 1", &mut log), " = 3");
 }
+
+#[test]
+fn reserved_names() {
+    let mut log = Log::new();
+    assert_eq!(
+        calculate_assign("#pi = 3", &mut log),
+        CalculatorError::ReservedName(String::from("pi")).to_string()
+    );
+    assert_eq!(
+        calculate_assign("#sin = 2", &mut log),
+        CalculatorError::ReservedName(String::from("sin")).to_string()
+    );
+    assert_eq!(
+        calculate("#clear = 2", &log),
+        CalculatorError::ReservedName(String::from("clear")).to_string()
+    );
+    assert_eq!(calculate("pi", &log), " = 3.14159265358979");
+}
+
+#[test]
+fn invalid_names() {
+    let mut log = Log::new();
+    assert_eq!(
+        calculate_assign("# = 3", &mut log),
+        CalculatorError::InvalidName(String::new()).to_string()
+    );
+    assert_eq!(
+        calculate("#1a = 3", &log),
+        CalculatorError::InvalidName(String::from("1a")).to_string()
+    );
+    assert_eq!(
+        calculate("#PI = 3", &log),
+        CalculatorError::ReservedName(String::from("PI")).to_string()
+    );
+}
+
+#[test]
+fn identifiers() {
+    let mut log = Log::new();
+    calculate_assign("#v1 = 3", &mut log);
+    calculate_assign("#max_speed = 10", &mut log);
+    calculate_assign("#M = 2", &mut log);
+    calculate_assign("#m = 5", &mut log);
+    assert_eq!(calculate("v1 * max_speed", &log), " = 30");
+    assert_eq!(calculate("M + m", &log), " = 7");
+    assert_eq!(
+        calculate("_x", &log),
+        CalculatorError::UnknownSymbol(String::from("_x")).to_string()
+    );
+    assert_eq!(calculate("Sin(Pi / 2)", &log), " = 1");
+}
+
+#[test]
+fn angle_modes() {
+    let mut log = Log::new();
+    assert_eq!(calculate("sin(90°)", &log), " = 1");
+    assert_eq!(
+        calculate_assign("/deg", &mut log),
+        "Angles are now in degrees"
+    );
+    assert_eq!(calculate("sin(90)", &log), " = 1");
+    assert_eq!(calculate("asin(1)", &log), " = 90");
+    assert_eq!(calculate("90°", &log), " = 90");
+    calculate_assign("/grad", &mut log);
+    assert_eq!(calculate("acos(-1)", &log), " = 200");
+    assert_eq!(calculate("90°", &log), " = 100");
+    calculate_assign("/rad", &mut log);
+    assert_eq!(calculate("acos(-1)", &log), " = 3.14159265358979");
+}
+
+#[test]
+fn smart_format() {
+    let log = Log::new();
+    assert_eq!(calculate("0.1 + 0.2", &log), " = 0.3");
+    assert_eq!(calculate("1.1 * 1.1", &log), " = 1.21");
+    assert_eq!(calculate("10^20", &log), " = 1e20");
+    assert_eq!(calculate("[0.1 + 0.2, 2]", &log), " = [0.3, 2]");
+    assert_eq!(calculate("sin(30°)", &log), " = 0.5");
+    // Whole numbers up to 2^53 are exact, so they are shown in full
+    assert_eq!(calculate("2^53", &log), " = 9007199254740992");
+    assert_eq!(calculate("-2^53", &log), " = -9007199254740992");
+    assert_eq!(calculate("10^15", &log), " = 1000000000000000");
+    assert_eq!(calculate("2^54", &log), " = 1.8014398509482e16");
+    assert_eq!(
+        calculate("nextprime(9007199254740847)", &log),
+        " = 9007199254740881"
+    );
+    assert_eq!(
+        calculate("factor(9007199254740881)", &log),
+        " = [9007199254740881]"
+    );
+}
+
+#[test]
+fn exponent_literals() {
+    let mut log = Log::new();
+    assert_eq!(calculate("2e3", &log), " = 2000");
+    assert_eq!(calculate("1.5E-3 * 2", &log), " = 0.003");
+    assert_eq!(calculate("1e+2", &log), " = 100");
+    // Without digits after it, e is still Euler's number
+    assert_close("2e", 2.0 * std::f64::consts::E, &log);
+    assert_close("2e^2", 2.0 * std::f64::consts::E.powi(2), &log);
+    assert_close("2e - 1", 2.0 * std::f64::consts::E - 1.0, &log);
+    assert_eq!(calculate("2e-1", &log), " = 0.2");
+    assert_eq!(calculate("2e1 = x", &log), " x = 20");
+    // Whatever is shown can be typed back in
+    calculate_assign("/sci 3", &mut log);
+    for n in [1e-8, 6.02214076e23, -0.000123, 12345.0] {
+        let shown = log.format.format_f64(n);
+        assert_eq!(calculate(&shown, &log), format!(" = {}", shown));
+    }
+    calculate_assign("/smart", &mut log);
+    for n in [1e-8, 6.02214076e23, -1.5e-300, 1e20] {
+        let shown = log.format.format_f64(n);
+        assert_eq!(parse(shown.chars(), &log), Ok(Scalar(n)), "{}", shown);
+    }
+}
+
+#[test]
+fn format_commands() {
+    let mut log = Log::new();
+    calculate_assign("/fix 2", &mut log);
+    assert_eq!(calculate("1 / 3", &log), " = 0.33");
+    calculate_assign("/sig 3", &mut log);
+    assert_eq!(calculate("12345", &log), " = 12300");
+    assert_eq!(calculate("0.0012345", &log), " = 0.00123");
+    calculate_assign("/sci 2", &mut log);
+    assert_eq!(calculate("12345", &log), " = 1.23e4");
+    calculate_assign("/eng 1", &mut log);
+    assert_eq!(calculate("12345", &log), " = 12.3e3");
+    assert_eq!(calculate("999999", &log), " = 1.0e6");
+    calculate_assign("/smart", &mut log);
+    calculate_assign("/group", &mut log);
+    assert_eq!(calculate("-1234567.5", &log), " = -1,234,567.5");
+    assert_eq!(
+        calculate_assign("/fix", &mut log),
+        CalculatorError::InvalidCommandArgument(String::from("fix")).to_string()
+    );
+    // Digit counts are limited to what an f64 can hold
+    for (command, name) in [
+        ("/fix 100", "fix"),
+        ("/sci 17", "sci"),
+        ("/sig 0", "sig"),
+        ("/sig 18", "sig"),
+        ("/eng 17", "eng"),
+    ] {
+        assert_eq!(
+            calculate_assign(command, &mut log),
+            CalculatorError::InvalidCommandArgument(name.to_owned()).to_string()
+        );
+    }
+    assert_eq!(
+        calculate_assign("/sig 17", &mut log),
+        "Numbers are now shown in 17 significant figures"
+    );
+}
+
+#[test]
+fn radix_literals() {
+    let log = Log::new();
+    assert_eq!(calculate("0xff + 0b101 + 0o17", &log), " = 275");
+    assert_eq!(calculate("0x10 * 2", &log), " = 32");
+    assert_eq!(calculate("0xfffffffff", &log), " = 68719476735");
+    assert_eq!(
+        calculate("0xffffffffffffffff", &log),
+        CalculatorError::ParseNumberErrror.to_string()
+    );
+}
+
+#[test]
+fn bitwise_operators() {
+    let log = Log::new();
+    assert_eq!(calculate("6 & 3", &log), " = 2");
+    assert_eq!(calculate("6 | 3", &log), " = 7");
+    assert_eq!(calculate("6 xor 3", &log), " = 5");
+    assert_eq!(calculate("~0", &log), " = -1");
+    assert_eq!(calculate("1 << 4", &log), " = 16");
+    assert_eq!(calculate("256 >> 4", &log), " = 16");
+    // Shifts bind looser than +, & looser than shifts, xor looser than &, | loosest
+    assert_eq!(calculate("1 << 2 + 1", &log), " = 8");
+    assert_eq!(calculate("1 | 2 xor 3 & 6", &log), " = 1");
+    assert_eq!(
+        calculate("1.5 & 1", &log),
+        CalculatorError::IntegerRequired(String::from("bitwise operator")).to_string()
+    );
+}
+
+#[test]
+fn programmer_mode() {
+    let mut log = Log::new();
+    calculate_assign("/int u8", &mut log);
+    assert_eq!(calculate_assign("255 + 1", &mut log), " = 0");
+    assert_eq!(calculate("~0", &log), " = 0xff | 255 | 0b11111111");
+    calculate_assign("/int i8 checked", &mut log);
+    assert_eq!(
+        calculate("127 + 1", &log),
+        CalculatorError::IntegerOverflow(String::from("i8 checked")).to_string()
+    );
+    assert_eq!(calculate("-1", &log), " = 0xff | -1 | 0b11111111");
+    calculate_assign("/int u32", &mut log);
+    assert_eq!(
+        calculate("0xffffffff", &log),
+        " = 0xffffffff | 4294967295 | 0b11111111111111111111111111111111"
+    );
+    calculate_assign("/int u64", &mut log);
+    let all_ones = format!(
+        " = 0xffffffffffffffff | 18446744073709551615 | 0b{}",
+        "1".repeat(64)
+    );
+    assert_eq!(calculate("0xffffffffffffffff", &log), all_ones);
+    assert_eq!(calculate("~0", &log), all_ones);
+    assert_eq!(calculate("-1", &log), all_ones);
+    assert_eq!(calculate_assign("0xffffffffffffffff + 1", &mut log), " = 0");
+    assert_eq!(
+        calculate_assign("0xffffffffffffffff * 0xffffffffffffffff", &mut log),
+        " = 1"
+    );
+    assert_eq!(
+        calculate_assign("2^53 + 1", &mut log),
+        " = 9007199254740993"
+    );
+    assert_eq!(calculate_assign("\\ - 2^53", &mut log), " = 1");
+    assert_eq!(
+        calculate_assign("0x8000000000000000 | 1", &mut log),
+        " = 9223372036854775809"
+    );
+    assert_eq!(
+        calculate_assign("0xffffffffffffffff xor 0xf0f0f0f0f0f0f0f0", &mut log),
+        " = 1085102592571150095"
+    );
+    assert_eq!(
+        calculate_assign("0xffffffffffffffff >> 60", &mut log),
+        " = 15"
+    );
+    assert_eq!(
+        calculate_assign("1 << 63", &mut log),
+        " = 9223372036854775808"
+    );
+    assert_eq!(
+        calculate_assign("#m = 18446744073709551615", &mut log),
+        " = 18446744073709551615"
+    );
+    assert_eq!(
+        calculate_assign("m // 2", &mut log),
+        " = 9223372036854775807"
+    );
+    calculate_assign("/int i64", &mut log);
+    assert_eq!(
+        calculate("0x7fffffffffffffff", &log),
+        format!(
+            " = 0x7fffffffffffffff | 9223372036854775807 | 0b{}",
+            "1".repeat(63)
+        )
+    );
+    assert_eq!(
+        calculate_assign("2^62 + 1", &mut log),
+        " = 4611686018427387905"
+    );
+    assert_eq!(
+        calculate_assign("0x7fffffffffffffff + 1", &mut log),
+        " = -9223372036854775808"
+    );
+    assert_eq!(calculate_assign("0xffffffffffffffff / 2", &mut log), " = 0");
+    assert_eq!(calculate_assign("m", &mut log), " = -1");
+    calculate_assign("/int i64 checked", &mut log);
+    assert_eq!(
+        calculate_assign("-9223372036854775808", &mut log),
+        " = -9223372036854775808"
+    );
+    assert_eq!(
+        calculate("0x7fffffffffffffff + 1", &log),
+        CalculatorError::IntegerOverflow(String::from("i64 checked")).to_string()
+    );
+    assert_eq!(
+        calculate("3037000500^2", &log),
+        CalculatorError::IntegerOverflow(String::from("i64 checked")).to_string()
+    );
+    assert_eq!(calculate_assign("7 / 2 + sqrt(16)", &mut log), " = 7");
+    assert_eq!(
+        calculate("1 / 0", &log),
+        CalculatorError::DivisionByZero.to_string()
+    );
+    calculate_assign("/int off", &mut log);
+    calculate_assign("/base hex", &mut log);
+    assert_eq!(calculate("255", &log), " = 0xff");
+    assert_eq!(calculate("2.5", &log), " = 2.5");
+}
+
+#[test]
+fn modulo() {
+    let log = Log::new();
+    assert_eq!(calculate("10 % 3", &log), " = 1");
+    assert_eq!(calculate("10 mod 3", &log), " = 1");
+    assert_eq!(calculate("mod(-7, 3)", &log), " = 2");
+    assert_eq!(calculate("2 * mod(7, 4) mod 4", &log), " = 2");
+    assert_eq!(calculate("-7 % 3", &log), " = 2");
+    assert_eq!(calculate("7 % -3", &log), " = -2");
+    assert_eq!(calculate("2 * 7 % 4", &log), " = 2");
+    assert_eq!(calculate("[5, 6, 7] % 3", &log), " = [2, 0, 1]");
+    assert_eq!(
+        calculate("1 % 0", &log),
+        CalculatorError::DivisionByZero.to_string()
+    );
+}
+
+#[test]
+fn floor_division() {
+    let log = Log::new();
+    assert_eq!(calculate("7 // 2", &log), " = 3");
+    assert_eq!(calculate("-7 // 2", &log), " = -4");
+    assert_eq!(calculate("6 / -2", &log), " = -3");
+    assert_eq!(calculate("[7, 8] // 2", &log), " = [3, 4]");
+    assert_eq!(
+        calculate("7 // 0", &log),
+        CalculatorError::DivisionByZero.to_string()
+    );
+}
+
+#[test]
+fn remainders() {
+    let log = Log::new();
+    assert_eq!(calculate("rem(-7, 3)", &log), " = -1");
+    assert_eq!(calculate("rem_euclid(-7, 3)", &log), " = 2");
+    assert_eq!(calculate("rem_euclid(-7, -3)", &log), " = 2");
+    assert_eq!(calculate("rem([-7, 7], 3)", &log), " = [-1, 1]");
+    assert_eq!(
+        calculate("rem(1, 0)", &log),
+        CalculatorError::DivisionByZero.to_string()
+    );
+}
+
+#[test]
+fn malformed_inputs() {
+    let log = Log::new();
+    let malformed = [
+        "10 = 3", "2 $ 3", "(1 + 2))", "[1, 2)", "1 + 2]", "3 # 4", "1 < 2", "{1}", "2 ; 3",
+        "4 ? 5", "'6'", "\"7\"", "8 @ 9", "sin 3", "(1, 2]", "(1 + 1", "[1, 2", "sin(1",
+        "(1+2)*(3",
+    ];
+    for input in malformed {
+        assert!(
+            calculate(input, &log).starts_with("Error"),
+            "{} should be rejected",
+            input
+        );
+    }
+}
+
+#[test]
+fn error_positions() {
+    let mut log = Log::new();
+    assert_eq!(
+        calculate("1 + (2 = 3)", &log),
+        CalculatorError::UnexpectedCharacter('=', 8).to_string()
+    );
+    assert_eq!(
+        calculate("(1 + 2))", &log),
+        CalculatorError::UnbalancedBracket(')', 8).to_string()
+    );
+    assert_eq!(
+        calculate_assign("#a = [1, 2)", &mut log),
+        CalculatorError::UnbalancedBracket(')', 11).to_string()
+    );
+    assert_eq!(
+        calculate("(1+2)*(3", &log),
+        CalculatorError::UnbalancedBracket('(', 7).to_string()
+    );
+    assert_eq!(
+        calculate("[1, (2", &log),
+        CalculatorError::UnbalancedBracket('(', 5).to_string()
+    );
+    assert_eq!(
+        calculate("  #a = 1 $", &log),
+        CalculatorError::UnexpectedCharacter('$', 10).to_string()
+    );
+}
+
+#[test]
+fn nested_brackets() {
+    let log = Log::new();
+    assert_eq!(calculate("(1 + (2 * (3 - 1))) * 2", &log), " = 10");
+    assert_eq!(calculate("log(abs(-100), 10) + 1", &log), " = 3");
+    assert_eq!(calculate("[abs(-1), log(8, 2)]", &log), " = [1, 3]");
+    assert_eq!(calculate("mag([3, 4]) * 2", &log), " = 10");
+}
+
+#[test]
+fn implicit_multiplication() {
+    let mut log = Log::new();
+    calculate_assign("#x = 3", &mut log);
+    assert_eq!(calculate("2x", &log), " = 6");
+    assert_eq!(calculate("2(1 + 1)", &log), " = 4");
+    assert_eq!(calculate("2x^2", &log), " = 18");
+    assert_eq!(calculate("2^-1", &log), " = 0.5");
+    assert_eq!(calculate("-2^2", &log), " = -4");
+    // Numbers next to each other only multiply without a space between them
+    assert_eq!(
+        calculate("12 34", &log),
+        CalculatorError::UnexpectedCharacter('3', 4).to_string()
+    );
+    assert_eq!(
+        calculate("[1, x 2]", &log),
+        CalculatorError::UnexpectedCharacter('2', 7).to_string()
+    );
+    assert_eq!(calculate("2 x", &log), " = 6");
+    assert_eq!(calculate("7 mod 4", &log), " = 3");
+}
+
+// Checks a calculation against a known value, for results which aren't exact
+fn assert_close(input: &str, expected: f64, log: &Log) {
+    assert_within(input, expected, 1e-12, log);
+}
+
+// Numerical methods only get so close, so they are checked with a relative tolerance
+fn assert_within(input: &str, expected: f64, tolerance: f64, log: &Log) {
+    match parse(input.chars(), log) {
+        Ok(Scalar(n)) => assert!(
+            (n - expected).abs() <= tolerance * expected.abs().max(1.0),
+            "{} = {}, expected {}",
+            input,
+            n,
+            expected
+        ),
+        other => panic!("{} gave {:?}", input, other),
+    }
+}
+
+#[test]
+fn factorials() {
+    let log = Log::new();
+    assert_eq!(calculate("5!", &log), " = 120");
+    assert_eq!(calculate("0!", &log), " = 1");
+    assert_eq!(calculate("2^3!", &log), " = 64");
+    assert_eq!(calculate("-3! + 1", &log), " = -5");
+    assert_eq!(calculate("[3, 4]!", &log), " = [6, 24]");
+    assert_close("0.5!", 0.886_226_925_452_758, &log);
+    assert_eq!(
+        calculate("(-2)!", &log),
+        CalculatorError::DomainError(String::from("factorial")).to_string()
+    );
+}
+
+// Reference values from Abramowitz and Stegun and the DLMF
+#[test]
+fn special_functions() {
+    let log = Log::new();
+    assert_close("gamma(0.5)", PI.sqrt(), &log);
+    assert_close("gamma(1.5)", 0.886_226_925_452_758, &log);
+    assert_close("gamma(-1.5)", 2.363_271_801_207_355, &log);
+    assert_close("gamma(6)", 120.0, &log);
+    assert_close("lgamma(100)", 359.134_205_369_575_4, &log);
+    assert_close("lgamma(0.5)", 0.572_364_942_924_700_1, &log);
+    assert_within("beta(2, 3)", 1.0 / 12.0, 0.0, &log);
+    assert_close("beta(200, 1)", 0.005, &log);
+    assert_close("beta(0.5, 0.5)", PI, &log);
+    assert_close("erf(0.5)", 0.520_499_877_813_046_5, &log);
+    assert_close("erf(1)", 0.842_700_792_949_714_9, &log);
+    assert_close("erf(-2)", -0.995_322_265_018_952_7, &log);
+    assert_close("erfc(2)", 0.004_677_734_981_047_266, &log);
+    assert_close("erfc(5)", 1.537_459_794_428_035e-12, &log);
+    assert_close("gammainc(1, 2)", 1.0 - (-2.0f64).exp(), &log);
+    assert_close("gammaincc(3, 2)", 5.0 * (-2.0f64).exp(), &log);
+    assert_eq!(
+        calculate("gamma(-2)", &log),
+        CalculatorError::DomainError(String::from("gamma")).to_string()
+    );
+    assert_eq!(
+        calculate("lgamma(0)", &log),
+        CalculatorError::DomainError(String::from("lgamma")).to_string()
+    );
+}
+
+#[test]
+fn combinatorics() {
+    let log = Log::new();
+    assert_eq!(calculate("ncr(5, 2)", &log), " = 10");
+    assert_eq!(calculate("ncr(52, 5)", &log), " = 2598960");
+    assert_eq!(calculate("ncr(3, 5)", &log), " = 0");
+    assert_eq!(calculate("npr(5, 2)", &log), " = 20");
+    assert_eq!(
+        calculate("ncr(5.5, 2)", &log),
+        CalculatorError::IntegerRequired(String::from("ncr")).to_string()
+    );
+    assert_eq!(
+        calculate("npr(5)", &log),
+        CalculatorError::MissingFunctionParameters(String::from("npr")).to_string()
+    );
+}
+
+#[test]
+fn number_theory() {
+    let log = Log::new();
+    assert_eq!(calculate("gcd(12, 18)", &log), " = 6");
+    assert_eq!(calculate("gcd(12, 18, 8)", &log), " = 2");
+    assert_eq!(calculate("gcd(-2^53, 2)", &log), " = 2");
+    // Past 2^53 not every whole number can be stored, so answers could be wrong
+    for input in [
+        "gcd(2^63, 6)",
+        "isprime(18446744073709551557)",
+        "nextprime(2^53)",
+        "nextprime(2^53 - 1)",
+    ] {
+        let name = &input[..input.find('(').unwrap()];
+        assert_eq!(
+            calculate(input, &log),
+            CalculatorError::BeyondExactIntegers(name.to_owned()).to_string()
+        );
+    }
+    assert_eq!(
+        calculate("lcm(2^52, 3)", &log),
+        CalculatorError::BeyondExactIntegers(String::from("lcm")).to_string()
+    );
+    assert_eq!(calculate("lcm(4, 6)", &log), " = 12");
+    assert_eq!(calculate("isprime(97)", &log), " = 1");
+    assert_eq!(calculate("isprime(91)", &log), " = 0");
+    assert_eq!(calculate("isprime(2^31 - 1)", &log), " = 1");
+    assert_eq!(calculate("nextprime(100)", &log), " = 101");
+    assert_eq!(calculate("factor(360)", &log), " = [2, 2, 2, 3, 3, 5]");
+    assert_eq!(
+        calculate("factor(600851475143)", &log),
+        " = [71, 839, 1471, 6857]"
+    );
+    assert_eq!(
+        calculate("factor(999985999949)", &log),
+        " = [999983, 1000003]"
+    );
+    assert_eq!(calculate("totient(36)", &log), " = 12");
+    assert_eq!(calculate("modpow(4, 13, 497)", &log), " = 445");
+    assert_eq!(calculate("modinv(3, 11)", &log), " = 4");
+    assert_eq!(calculate("modpow(3, -1, 11)", &log), " = 4");
+    assert_eq!(
+        calculate("modinv(2, 4)", &log),
+        CalculatorError::DomainError(String::from("modinv")).to_string()
+    );
+    assert_eq!(
+        calculate("gcd(1.5, 3)", &log),
+        CalculatorError::IntegerRequired(String::from("gcd")).to_string()
+    );
+}
+
+#[test]
+fn statistics() {
+    let log = Log::new();
+    assert_eq!(calculate("sum([1, 2, 3, 4])", &log), " = 10");
+    assert_eq!(calculate("sum(1, 2, 3, 4)", &log), " = 10");
+    assert_eq!(calculate("prod(1, 2, 3, 4)", &log), " = 24");
+    assert_eq!(calculate("mean([2, 4, 4, 4, 5, 5, 7, 9])", &log), " = 5");
+    assert_eq!(calculate("median(3, 1, 2)", &log), " = 2");
+    assert_eq!(calculate("median([4, 1, 3, 2])", &log), " = 2.5");
+    assert_eq!(calculate("mode(1, 2, 2, 3, 3)", &log), " = 2");
+    assert_eq!(calculate("min(3, -1, 2)", &log), " = -1");
+    assert_eq!(calculate("max([3, -1, 2])", &log), " = 3");
+    assert_eq!(calculate("range(3, -1, 2)", &log), " = 4");
+    assert_eq!(calculate("varp([2, 4, 4, 4, 5, 5, 7, 9])", &log), " = 4");
+    assert_eq!(calculate("stdevp([2, 4, 4, 4, 5, 5, 7, 9])", &log), " = 2");
+    assert_eq!(calculate("var(1, 2, 3, 4)", &log), " = 1.66666666666667");
+    assert_eq!(calculate("stdev(2, 4)", &log), " = 1.4142135623731");
+    assert_eq!(calculate("percentile([1, 2, 3, 4, 5], 25)", &log), " = 2");
+    assert_eq!(calculate("percentile([1, 2, 3, 4], 50)", &log), " = 2.5");
+    assert_eq!(
+        calculate("zscore(6, [2, 4, 6, 8])", &log),
+        " = 0.387298334620742"
+    );
+    assert_eq!(
+        calculate("zscore([1, 3])", &log),
+        " = [-0.707106781186547, 0.707106781186547]"
+    );
+    for input in ["zscore(2, [1, 1, 1])", "zscore([5, 5])"] {
+        assert_eq!(
+            calculate(input, &log),
+            CalculatorError::DomainError(String::from("zscore")).to_string()
+        );
+    }
+    assert_eq!(
+        calculate("mean([])", &log),
+        CalculatorError::EmptyInput(String::from("mean")).to_string()
+    );
+    assert_eq!(
+        calculate("max()", &log),
+        CalculatorError::EmptyInput(String::from("max")).to_string()
+    );
+}
+
+#[test]
+fn vector_geometry() {
+    let mut log = Log::new();
+    assert_eq!(calculate("dot([1, 2, 3], [4, 5, 6])", &log), " = 32");
+    assert_eq!(
+        calculate("cross([1, 0, 0], [0, 1, 0])", &log),
+        " = [0, 0, 1]"
+    );
+    assert_eq!(calculate("normalize([3, 4])", &log), " = [0.6, 0.8]");
+    assert_eq!(calculate("NORMALIZE([0, 2])", &log), " = [0, 1]");
+    assert_eq!(calculate("proj([2, 3], [1, 0])", &log), " = [2, 0]");
+    assert_eq!(calculate("reject([2, 3], [1, 0])", &log), " = [0, 3]");
+    assert_eq!(calculate("reflect([1, -1], [0, 1])", &log), " = [1, 1]");
+    assert_eq!(calculate("dist([1, 1], [4, 5])", &log), " = 5");
+    calculate_assign("/deg", &mut log);
+    assert_eq!(calculate("angle([1, 0], [0, 3])", &log), " = 90");
+    for input in ["cross([1, 0], [0, 1])", "cross([1, 0, 0], [0, 1])"] {
+        assert_eq!(
+            calculate(input, &log),
+            CalculatorError::CrossNeeds3D.to_string()
+        );
+    }
+    assert_eq!(
+        calculate("norm([3, 4])", &log),
+        CalculatorError::UnknownSymbol(String::from("norm")).to_string()
+    );
+    assert_eq!(
+        calculate("dot([1, 0], [0, 1, 2])", &log),
+        CalculatorError::ShapeMismatch(2, 3).to_string()
+    );
+    assert_eq!(
+        calculate("normalize([0, 0])", &log),
+        CalculatorError::DomainError(String::from("normalize")).to_string()
+    );
+}
+
+#[test]
+fn vector_shapes() {
+    let log = Log::new();
+    // Equal lengths work component by component
+    assert_eq!(calculate("[1, 2] + [3, 4]", &log), " = [4, 6]");
+    assert_eq!(calculate("[1, 2] - [3, 5]", &log), " = [-2, -3]");
+    assert_eq!(calculate("[1, 2] * [3, 4]", &log), " = [3, 8]");
+    assert_eq!(calculate("[3, 8] / [3, 4]", &log), " = [1, 2]");
+    assert_eq!(calculate("[2, 3] ^ [3, 2]", &log), " = [8, 9]");
+    assert_eq!(calculate("[7, 9] // [2, 4]", &log), " = [3, 2]");
+    assert_eq!(calculate("[7, 9] % [2, 4]", &log), " = [1, 1]");
+    assert_eq!(calculate("[6, 5] & [3, 4]", &log), " = [2, 4]");
+    assert_eq!(calculate("[1, 2] << [1, 2]", &log), " = [2, 8]");
+    // Scalars broadcast on either side
+    assert_eq!(calculate("2 + [1, 2]", &log), " = [3, 4]");
+    assert_eq!(calculate("[1, 2] - 1", &log), " = [0, 1]");
+    assert_eq!(calculate("8 / [2, 4]", &log), " = [4, 2]");
+    assert_eq!(calculate("2 ^ [2, 3]", &log), " = [4, 8]");
+    assert_eq!(calculate("[2, 3] ^ 2", &log), " = [4, 9]");
+    assert_eq!(calculate("7 % [2, 4]", &log), " = [1, 3]");
+    assert_eq!(calculate("1 | [2, 4]", &log), " = [3, 5]");
+    // Anything else is an error
+    let mismatch = CalculatorError::ShapeMismatch(3, 1).to_string();
+    for op in [
+        "+", "-", "*", "/", "^", "//", "%", "&", "|", "xor", "<<", ">>",
+    ] {
+        assert_eq!(
+            calculate(&format!("[1, 2, 3] {} [1]", op), &log),
+            mismatch,
+            "{} should need equal lengths",
+            op
+        );
+    }
+}
+
+#[test]
+fn lenient_vectors() {
+    let mut log = Log::new();
+    calculate_assign("/lenient", &mut log);
+    assert_eq!(calculate("[1, 2, 3] + [1]", &log), " = [2, 3, 4]");
+    assert_eq!(calculate("[1, 2, 3] * [2]", &log), " = [2, 2, 3]");
+    assert_eq!(calculate("[1, 2] - [1, 1, 1]", &log), " = [0, 1]");
+    calculate_assign("/lenient", &mut log);
+    assert_eq!(
+        calculate("[1, 2, 3] + [1]", &log),
+        CalculatorError::ShapeMismatch(3, 1).to_string()
+    );
+}
+
+#[test]
+fn ranges() {
+    let log = Log::new();
+    assert_eq!(calculate("1..5", &log), " = [1, 2, 3, 4, 5]");
+    assert_eq!(calculate("3..1", &log), " = [3, 2, 1]");
+    assert_eq!(calculate("-1..1", &log), " = [-1, 0, 1]");
+    assert_eq!(calculate("1..2 + 2", &log), " = [1, 2, 3, 4]");
+    assert_eq!(calculate("1.5..3", &log), " = [1.5, 2.5]");
+    assert_eq!(
+        calculate("0..1 step 0.25", &log),
+        " = [0, 0.25, 0.5, 0.75, 1]"
+    );
+    assert_eq!(calculate("0..1 step 0.1", &log).matches(',').count(), 10);
+    assert_eq!(calculate("10..0 step -5", &log), " = [10, 5, 0]");
+    assert_eq!(calculate("0..10 step -1", &log), " = []");
+    assert_eq!(calculate("2(1..3)", &log), " = [2, 4, 6]");
+    assert_eq!(calculate("sum(1..100)", &log), " = 5050");
+    assert_eq!(
+        calculate("0..1 step 0", &log),
+        CalculatorError::DomainError(String::from("step")).to_string()
+    );
+    assert_eq!(
+        calculate("1..1000000000", &log),
+        CalculatorError::VectorTooLong(1_000_000).to_string()
+    );
+    assert_eq!(
+        calculate("1 step 2", &log),
+        CalculatorError::UnknownSymbol(String::from("step")).to_string()
+    );
+}
+
+#[test]
+fn sequence_functions() {
+    let log = Log::new();
+    assert_eq!(
+        calculate("linspace(0, 1, 5)", &log),
+        " = [0, 0.25, 0.5, 0.75, 1]"
+    );
+    assert_eq!(calculate("linspace(2, 2, 1)", &log), " = [2]");
+    assert_eq!(calculate("linspace(0, 1, 0)", &log), " = []");
+    assert_eq!(calculate("repeat(7, 3)", &log), " = [7, 7, 7]");
+    assert_eq!(calculate("repeat([1, 2], 2)", &log), " = [1, 2, 1, 2]");
+    assert_eq!(calculate("concat([1, 2], [3], 4)", &log), " = [1, 2, 3, 4]");
+    assert_eq!(calculate("concat(1..2, 5..4)", &log), " = [1, 2, 5, 4]");
+    assert_eq!(
+        calculate("repeat(1, 1.5)", &log),
+        CalculatorError::IntegerRequired(String::from("repeat")).to_string()
+    );
+}
+
+#[test]
+fn lambdas() {
+    let mut log = Log::new();
+    assert_eq!(calculate("x -> x^2", &log), " = x -> x^2");
+    assert_eq!(calculate("(a, b)->a + b", &log), " = (a, b) -> a + b");
+    calculate_assign("#sq = x -> x^2", &mut log);
+    calculate_assign("#add = (a, b) -> a + b", &mut log);
+    assert_eq!(calculate("sq(3)", &log), " = 9");
+    assert_eq!(calculate("2sq(3) + 1", &log), " = 19");
+    assert_eq!(calculate("add(sq(2), 1)", &log), " = 5");
+    assert_eq!(calculate("(() -> 7)", &log), " = () -> 7");
+    // Parameters don't leak out or overwrite variables
+    calculate_assign("#x = 10", &mut log);
+    assert_eq!(calculate("sq(2) + x", &log), " = 14");
+    // Lambdas see the variables around them
+    calculate_assign("#shift = y -> y + x", &mut log);
+    assert_eq!(calculate("shift(1)", &log), " = 11");
+    assert_eq!(
+        calculate("add(1)", &log),
+        CalculatorError::MissingFunctionParameters(String::from("(a, b) -> a + b")).to_string()
+    );
+    assert_eq!(
+        calculate("sq + 1", &log),
+        CalculatorError::FunctionAsNumber.to_string()
+    );
+    assert_eq!(
+        calculate("-sq", &log),
+        CalculatorError::FunctionAsNumber.to_string()
+    );
+    assert_eq!(
+        calculate("pi -> 2", &log),
+        CalculatorError::ReservedName(String::from("pi")).to_string()
+    );
+    calculate_assign("#loop = n -> loop(n)", &mut log);
+    assert_eq!(
+        calculate("loop(1)", &log),
+        CalculatorError::RecursionLimit.to_string()
+    );
+}
+
+#[test]
+fn higher_order_functions() {
+    let mut log = Log::new();
+    assert_eq!(calculate("map([1, 2, 3], x -> x^2)", &log), " = [1, 4, 9]");
+    assert_eq!(calculate("map([1, 4, 9], sqrt)", &log), " = [1, 2, 3]");
+    assert_eq!(calculate("map([-1, 2], ABS)", &log), " = [1, 2]");
+    assert_eq!(calculate("filter(1..6, x -> x % 2)", &log), " = [1, 3, 5]");
+    assert_eq!(
+        calculate("reduce([1, 2, 3, 4], (a, b) -> a * b)", &log),
+        " = 24"
+    );
+    assert_eq!(calculate("reduce([5], (a, b) -> a * b)", &log), " = 5");
+    assert_eq!(
+        calculate("fold([1, 2, 3], 10, (a, b) -> a - b)", &log),
+        " = 4"
+    );
+    assert_eq!(
+        calculate("fold([1, 2], [0, 0], (a, v) -> a + v)", &log),
+        " = [3, 3]"
+    );
+    assert_eq!(
+        calculate("zip([1, 2], [3, 4], (a, b) -> a * b)", &log),
+        " = [3, 8]"
+    );
+    assert_eq!(
+        calculate("sort_by([3, -1, 2], x -> -x)", &log),
+        " = [3, 2, -1]"
+    );
+    assert_eq!(calculate("sort_by([-3, 1, 2], abs)", &log), " = [1, 2, -3]");
+    calculate_assign("#cube = x -> x^3", &mut log);
+    assert_eq!(calculate("map([1, 2], cube)", &log), " = [1, 8]");
+    assert_eq!(
+        calculate("reduce([], (a, b) -> a)", &log),
+        CalculatorError::EmptyInput(String::from("reduce")).to_string()
+    );
+    assert_eq!(
+        calculate("zip([1], [1, 2], (a, b) -> a)", &log),
+        CalculatorError::ShapeMismatch(1, 2).to_string()
+    );
+    assert_eq!(
+        calculate("map([1, 2], x -> [x])", &log),
+        CalculatorError::RecursiveVectors.to_string()
+    );
+    assert_eq!(
+        calculate("map([1, 2], 3)", &log),
+        CalculatorError::MissingFunctionParameters(String::from("map")).to_string()
+    );
+}
+
+#[test]
+fn series() {
+    let mut log = Log::new();
+    assert_eq!(calculate("sum(k, 1, 100, k)", &log), " = 5050");
+    assert_eq!(calculate("sum(k, 1, 10, k^2)", &log), " = 385");
+    assert_eq!(calculate("prod(k, 1, 5, k)", &log), " = 120");
+    assert_eq!(calculate("sum(i, 0, 2, [i, 1])", &log), " = [3, 3]");
+    assert_close("sum(n, 0, 20, 1 / n!)", std::f64::consts::E, &log);
+    // Empty series
+    assert_eq!(calculate("sum(k, 5, 1, k)", &log), " = 0");
+    assert_eq!(calculate("prod(k, 5, 1, k)", &log), " = 1");
+    // Bounds and the expression can use variables, the series variable is only used inside
+    calculate_assign("#n = 4", &mut log);
+    assert_eq!(calculate("sum(k, 1, n, k * n)", &log), " = 40");
+    assert_eq!(
+        calculate("k", &log),
+        CalculatorError::UnknownSymbol(String::from("k")).to_string()
+    );
+    // Defining the series variable first doesn't change anything, the series hides it
+    calculate_assign("#f = m -> prod(k, 1, m, k)", &mut log);
+    calculate_assign("#k = 2", &mut log);
+    assert_eq!(calculate("sum(k, 1, 3, k)", &log), " = 6");
+    assert_eq!(calculate("f(4)", &log), " = 24");
+    assert_eq!(calculate("k", &log), " = 2");
+    assert_eq!(calculate("sum(j, 1, 3, sum(i, 1, j, i))", &log), " = 10");
+    // Anything else is still the ordinary sum and product
+    assert_eq!(calculate("sum(1, 2, 3, 4)", &log), " = 10");
+    assert_eq!(calculate("sum([1, 2], 3)", &log), " = 6");
+    assert_eq!(calculate("prod(2, 3)", &log), " = 6");
+    assert_eq!(calculate("map([1, 2], x -> sum(x, 1))", &log), " = [2, 3]");
+    assert_eq!(calculate("reduce([2, 3, 4], prod)", &log), " = 24");
+    assert_eq!(
+        calculate("sum(k, 1, 2.5, k)", &log),
+        CalculatorError::IntegerRequired(String::from("sum")).to_string()
+    );
+    assert_eq!(
+        calculate("sum(k, 1, 10000000000, k)", &log),
+        CalculatorError::DomainError(String::from("sum")).to_string()
+    );
+}
+
+#[test]
+fn derivatives() {
+    let mut log = Log::new();
+    assert_within("deriv(x^2, x, 3)", 6.0, 1e-9, &log);
+    assert_within("deriv(sin(x), x, 0)", 1.0, 1e-9, &log);
+    assert_within("deriv(sin(x), x, 1000)", 1000f64.cos(), 1e-9, &log);
+    assert_within("deriv(ln(x), x, 0.01)", 100.0, 1e-9, &log);
+    assert_within("deriv(e^x, x, 10)", 10f64.exp(), 1e-9, &log);
+    // Higher orders
+    assert_within("deriv(e^x, x, 1, 2)", std::f64::consts::E, 1e-8, &log);
+    assert_within("deriv(x^4, x, 1, 4)", 24.0, 1e-6, &log);
+    assert_within("deriv(x^3, x, 2, 0)", 8.0, 0.0, &log);
+    // User functions and variables
+    calculate_assign("#f = t -> t^3", &mut log);
+    calculate_assign("#a = 2", &mut log);
+    assert_within("deriv(f(x), x, a)", 12.0, 1e-9, &log);
+    assert_within("deriv(a * x, x, 5)", 2.0, 1e-9, &log);
+    // The error estimate is small when the derivative exists
+    let error = parse("deriv_err(sin(x), x, 1)".chars(), &log).unwrap();
+    assert!(error.scalar_value().unwrap() < 1e-9);
+    let v = parse("deriv(x^2, x, [1, 2])".chars(), &log).unwrap();
+    match v {
+        Vector(v) => assert!((v[0] - 2.0).abs() < 1e-9 && (v[1] - 4.0).abs() < 1e-9),
+        other => panic!("expected a vector, got {:?}", other),
+    }
+    assert_eq!(
+        calculate("deriv(1/x, x, 0)", &log),
+        CalculatorError::ConvergenceFailure(String::from("deriv")).to_string()
+    );
+    // The slopes either side of a kink don't match
+    for kink in [
+        "deriv(abs(x), x, 0)",
+        "deriv(abs(x - 2), x, 2)",
+        "deriv(max(x, 0), x, 0)",
+    ] {
+        assert_eq!(
+            calculate(kink, &log),
+            CalculatorError::ConvergenceFailure(String::from("deriv")).to_string()
+        );
+    }
+    assert_within("deriv(abs(x), x, 1)", 1.0, 1e-9, &log);
+    assert_within("deriv(1000 + x^2, x, 1)", 2.0, 1e-9, &log);
+    assert_within("deriv(e^(10x), x, 0)", 10.0, 1e-9, &log);
+    assert_eq!(
+        calculate("deriv(x, x, 1, 5)", &log),
+        CalculatorError::DomainError(String::from("deriv")).to_string()
+    );
+    assert_eq!(
+        calculate("deriv(x, pi, 1)", &log),
+        CalculatorError::ReservedName(String::from("pi")).to_string()
+    );
+    assert_eq!(
+        calculate("deriv(x, x)", &log),
+        CalculatorError::MissingFunctionParameters(String::from("deriv")).to_string()
+    );
+}
+
+#[test]
+fn integrals() {
+    let mut log = Log::new();
+    assert_within("integrate(x^2, x, 0, 3)", 9.0, 1e-10, &log);
+    assert_within("integrate(sin(x), x, 0, pi)", 2.0, 1e-10, &log);
+    assert_within("integrate(sin(x), x, pi, 0)", -2.0, 1e-10, &log);
+    assert_within("integrate(x, x, 2, 2)", 0.0, 0.0, &log);
+    assert_within("integrate(1 / sqrt(x), x, 0, 1)", 2.0, 1e-8, &log);
+    // Infinite bounds
+    assert_within("integrate(e^-x, x, 0, inf)", 1.0, 1e-10, &log);
+    assert_within("integrate(1 / x^2, x, -inf, -1)", 1.0, 1e-10, &log);
+    assert_within(
+        "integrate(e^(-x^2), x, -inf, inf)",
+        std::f64::consts::PI.sqrt(),
+        1e-10,
+        &log,
+    );
+    // User functions and variables
+    calculate_assign("#f = t -> 3t^2", &mut log);
+    calculate_assign("#b = 2", &mut log);
+    assert_within("integrate(f(x), x, 0, b)", 8.0, 1e-10, &log);
+    assert_eq!(
+        calculate("integrate(1 / x, x, 0, 1)", &log),
+        CalculatorError::ConvergenceFailure(String::from("integrate")).to_string()
+    );
+    assert_eq!(
+        calculate("integrate(x, x, 0)", &log),
+        CalculatorError::MissingFunctionParameters(String::from("integrate")).to_string()
+    );
+}
+
+#[test]
+fn solving() {
+    let mut log = Log::new();
+    assert_within(
+        "solve(x^2 - 2, x, 1)",
+        std::f64::consts::SQRT_2,
+        1e-12,
+        &log,
+    );
+    assert_within(
+        "solve(x^2 - 2, x, -1)",
+        -std::f64::consts::SQRT_2,
+        1e-12,
+        &log,
+    );
+    assert_within(
+        "solve(cos(x) - x, x, 0, 1)",
+        0.7390851332151607,
+        1e-12,
+        &log,
+    );
+    calculate_assign("#f = t -> t^3 - 8", &mut log);
+    assert_within("solve(f(x), x, 0, 5)", 2.0, 1e-12, &log);
+    assert_eq!(
+        calculate("solve(x^2 + 1, x, 0, 1)", &log),
+        CalculatorError::NoRoots(String::from("x")).to_string()
+    );
+    // A change of sign at a pole isn't a root
+    for pole in ["solve(tan(x), x, 1, 2)", "solve(1/x, x, -1, 1)"] {
+        assert_eq!(
+            calculate(pole, &log),
+            CalculatorError::NoRoots(String::from("x")).to_string()
+        );
+    }
+    assert_eq!(
+        calculate("solve(x^2 + 1, x, 3)", &log),
+        CalculatorError::ConvergenceFailure(String::from("solve")).to_string()
+    );
+    assert_eq!(calculate("roots(x^2 - 4, x, -10, 10)", &log), " = [-2, 2]");
+    assert_eq!(calculate("roots(x^2 + 1, x, -10, 10)", &log), " = []");
+    assert_eq!(calculate("roots((x - 1)^2, x, -10, 10)", &log), " = [1]");
+}
+
+#[test]
+fn optimisation() {
+    let mut log = Log::new();
+    assert_within("minimize((x - 1)^2 + 3, x, -5, 5).x", 1.0, 1e-6, &log);
+    assert_within("minimize((x - 1)^2 + 3, x, -5, 5).y", 3.0, 1e-12, &log);
+    assert_within("maximize(sin(x), x, 0, pi).x", PI / 2.0, 1e-6, &log);
+    assert_within("maximize(sin(x), x, 0, pi).y", 1.0, 1e-12, &log);
+    // The lowest of several dips, not just the first one found
+    assert_within(
+        "minimize(x^4 - 3x^2 + x, x, -3, 3).x",
+        -1.300_839_565_434_887,
+        1e-6,
+        &log,
+    );
+    // Extremes at the ends of the interval
+    assert_within("minimize(x, x, 2, 3).x", 2.0, 1e-12, &log);
+    assert_within("maximize(x, x, 2, 3).y", 3.0, 1e-12, &log);
+    // User defined functions work inside the expression
+    calculate_assign("#f = t -> (t - 2)^2", &mut log);
+    assert_within("minimize(f(t), t, 0, 5).x", 2.0, 1e-6, &log);
+    assert_eq!(
+        calculate("minimize(1/x, x, -1, 1)", &log),
+        CalculatorError::ConvergenceFailure(String::from("minimize")).to_string()
+    );
+    assert_eq!(
+        calculate("maximize(x, x, 3, 2)", &log),
+        CalculatorError::DomainError(String::from("maximize")).to_string()
+    );
+}
+
+#[test]
+fn differential_equations() {
+    let mut log = Log::new();
+    assert_within("ode(y, 0, 1, 1)", std::f64::consts::E, 1e-9, &log);
+    assert_within("ode(2t, 0, 0, 3)", 9.0, 1e-9, &log);
+    // Backwards in time
+    assert_within("ode(y, 1, e, 0)", 1.0, 1e-9, &log);
+    assert_eq!(calculate("ode(y, 2, 5, 2)", &log), " = 5");
+    // A system, the harmonic oscillator y'' = -y as [position, velocity]
+    assert_within("ode([y.1, -y.0], 0, [0, 1], pi/2).0", 1.0, 1e-9, &log);
+    assert_within("ode([y.1, -y.0], 0, [0, 1], pi/2).1", 0.0, 1e-9, &log);
+    // t and y don't change any variables outside
+    calculate_assign("#y = 5", &mut log);
+    assert_within("ode(-2t y, 0, 1, 1)", (-1.0_f64).exp(), 1e-9, &log);
+    assert_eq!(calculate("y", &log), " = 5");
+
+    assert_within("ode_path(y, 0, 1, 1, 2).0", 1.0, 1e-12, &log);
+    assert_within("ode_path(y, 0, 1, 1, 2).1", 0.5_f64.exp(), 1e-9, &log);
+    assert_within("ode_path(y, 0, 1, 1, 2).2", std::f64::consts::E, 1e-9, &log);
+    assert_within("ode_path([y.1, -y.0], 0, [0, 1], pi, 2).2", 1.0, 1e-9, &log);
+    assert_within(
+        "ode_path([y.1, -y.0], 0, [0, 1], pi, 2).5",
+        -1.0,
+        1e-9,
+        &log,
+    );
+
+    // y' = y^2 from 1 goes to infinity at t = 1
+    assert_eq!(
+        calculate("ode(y^2, 0, 1, 2)", &log),
+        CalculatorError::ConvergenceFailure(String::from("ode")).to_string()
+    );
+    assert_eq!(
+        calculate("ode([y.0], 0, [1, 2], 1)", &log),
+        CalculatorError::ShapeMismatch(2, 1).to_string()
+    );
+    assert_eq!(
+        calculate("ode_path(y, 0, 1, 1, 0)", &log),
+        CalculatorError::DomainError(String::from("ode_path")).to_string()
+    );
+    assert_eq!(
+        calculate("ode_path([y.1, -y.0], 0, [0, 1], 1, 600000)", &log),
+        CalculatorError::VectorTooLong(1_000_000).to_string()
+    );
+}
+
+#[test]
+fn equations() {
+    let mut log = Log::new();
+    assert_eq!(calculate("x^2 + 3x = 10", &log), " x = [-5, 2]");
+    assert_eq!(calculate("2y + 1 = 7", &log), " y = 3");
+    assert_eq!(calculate("t^2 = 0", &log), " t = 0");
+    assert_eq!(
+        calculate("1 / z = 0", &log),
+        CalculatorError::NoRootsInRange(String::from("z"), -100.0, 100.0).to_string()
+    );
+    // Solutions outside of the search range are reported as such
+    assert_eq!(
+        calculate("x^2 = 20000", &log),
+        CalculatorError::NoRootsInRange(String::from("x"), -100.0, 100.0).to_string()
+    );
+    assert_eq!(
+        calculate("x = x", &log),
+        CalculatorError::EveryValueRoot(String::from("x")).to_string()
+    );
+    assert_eq!(
+        calculate("2(x + 1) = 2x + 2", &log),
+        CalculatorError::EveryValueRoot(String::from("x")).to_string()
+    );
+    // Known variables aren't unknowns
+    calculate_assign("#a = 4", &mut log);
+    assert_eq!(calculate("x^2 = a", &log), " x = [-2, 2]");
+    // The roots can be stored
+    assert_eq!(calculate_assign("#r = x^2 = 9", &mut log), " x = [-3, 3]");
+    assert_eq!(calculate("r", &log), " = [-3, 3]");
+    assert_eq!(calculate("\\", &log), " = [-3, 3]");
+    assert_eq!(
+        calculate("x + y = 1", &log),
+        CalculatorError::EquationUnknowns(2).to_string()
+    );
+    assert_eq!(
+        calculate("2 = 3", &log),
+        CalculatorError::EquationUnknowns(0).to_string()
+    );
+    assert_eq!(
+        calculate("x = 1 = 2", &log),
+        CalculatorError::UnexpectedCharacter('=', 7).to_string()
+    );
+    assert_eq!(
+        calculate("(x = 1)", &log),
+        CalculatorError::UnexpectedCharacter('=', 4).to_string()
+    );
+}
+
+#[test]
+fn polynomials() {
+    let mut log = Log::new();
+    assert_eq!(calculate("polyval([1, 0, -4], 3)", &log), " = 5");
+    assert_eq!(
+        calculate("polyval([2, 1], [0, 1, 2])", &log),
+        " = [1, 3, 5]"
+    );
+    assert_eq!(calculate("polyder([1, 2, 3])", &log), " = [2, 2]");
+    assert_eq!(calculate("polyder([0, 0, 5])", &log), " = []");
+    assert_eq!(calculate("polyint([6, 2])", &log), " = [3, 2, 0]");
+    assert_eq!(calculate("polyint([6, 2], 5)", &log), " = [3, 2, 5]");
+    assert_eq!(calculate("polymul([1, 1], [1, -1])", &log), " = [1, 0, -1]");
+    // Roots
+    assert_eq!(calculate("polyroots([1, 0, -4])", &log), " = [-2, 2]");
+    assert_eq!(calculate("polyroots([2, -6, 4])", &log), " = [1, 2]");
+    assert_eq!(calculate("polyroots([1, 0, 1])", &log), " = [-1i, 1i]");
+    assert_eq!(
+        calculate("polyroots([1, -2, 5])", &log),
+        " = [1 - 2i, 1 + 2i]"
+    );
+    assert_eq!(calculate("polyroots([1, -1, 0])", &log), " = [0, 1]");
+    assert_eq!(calculate("polyroots([1, 1e-300])", &log), " = [-1e-300]");
+    assert_eq!(calculate("polyroots([1e300, 1])", &log), " = [-1e-300]");
+    // Repeated roots
+    assert_eq!(
+        calculate("polyroots([1, -4, 6, -4, 1])", &log),
+        " = [1, 1, 1, 1]"
+    );
+    assert_eq!(calculate("polyroots([1, -3, 3, -1])", &log), " = [1, 1, 1]");
+    assert_eq!(calculate("polyroots([1, 0, -3, 2])", &log), " = [-2, 1, 1]");
+    assert_eq!(
+        calculate("polyroots(polymul([1, 0, 1], [1, 0, 1]))", &log),
+        " = [-1i, -1i, 1i, 1i]"
+    );
+    assert_eq!(
+        calculate("polyroots([1, -2.001, 1.001])", &log),
+        " = [1, 1.001]"
+    );
+    assert_eq!(calculate("re(polyroots([1, -2, 5]))", &log), " = [1, 1]");
+    // Components of complex results stay complex
+    calculate_assign("#z = polyroots([1, -2, 5])", &mut log);
+    assert_eq!(calculate("z.1", &log), " = [1 + 2i]");
+    assert_eq!(calculate("z[-1:]", &log), " = [1 + 2i]");
+    assert_eq!(calculate("im(z.0)", &log), " = [-2]");
+    assert_eq!(
+        calculate("z.2", &log),
+        CalculatorError::ComponentDNE.to_string()
+    );
+    assert_eq!(calculate("im(polyroots([1, -2, 5]))", &log), " = [-2, 2]");
+    assert_eq!(calculate("abs(polyroots([1, 0, 4]))", &log), " = [2, 2]");
+    assert_eq!(
+        calculate("2arg(polyroots([1, 0, 1])) / pi", &log),
+        " = [-1, 1]"
+    );
+    assert_eq!(calculate("arg([-2, 2]) / pi", &log), " = [1, 0]");
+    assert_eq!(
+        calculate("polyroots([1, 0, 1]) + 1", &log),
+        CalculatorError::ComplexNotSupported.to_string()
+    );
+    assert_eq!(
+        calculate("polyroots([0, 0])", &log),
+        CalculatorError::DomainError(String::from("polyroots")).to_string()
+    );
+    // Fitting
+    assert_within("polyfit([0, 1, 2], [1, 3, 5], 1).0", 2.0, 1e-12, &log);
+    assert_within("polyfit([0, 1, 2], [1, 3, 5], 1).1", 1.0, 1e-12, &log);
+    assert_within(
+        "polyfit([-1, 0, 1, 2], [1, 0, 1, 4], 2).0",
+        1.0,
+        1e-12,
+        &log,
+    );
+    assert_within(
+        "polyfit([-1, 0, 1, 2], [1, 0, 1, 4], 2).2",
+        0.0,
+        1e-12,
+        &log,
+    );
+    // More points than the degree needs, the line through (0, 0), (1, 1), (2, 1)
+    assert_within("polyfit([0, 1, 2], [0, 1, 1], 1).0", 0.5, 1e-12, &log);
+    assert_within("polyfit([0, 1, 2], [0, 1, 1], 1).1", 1.0 / 6.0, 1e-12, &log);
+    assert_eq!(
+        calculate("polyfit([1, 1], [1, 2], 1)", &log),
+        CalculatorError::DomainError(String::from("polyfit")).to_string()
+    );
+    assert_eq!(
+        calculate("polyfit([1, 2], [1], 1)", &log),
+        CalculatorError::ShapeMismatch(2, 1).to_string()
+    );
+}
+
+#[test]
+fn fitting() {
+    let mut log = Log::new();
+    assert_within("linreg([0, 1, 2], [1, 3, 5]).x", 2.0, 1e-12, &log);
+    assert_within("linreg([0, 1, 2], [1, 3, 5]).y", 1.0, 1e-12, &log);
+    assert_within("linreg([0, 1, 2], [1, 3, 5]).z", 1.0, 1e-12, &log);
+    // Slope 0.5, intercept 1/6 and r² 0.75
+    assert_within("linreg([0, 1, 2], [0, 1, 1]).z", 0.75, 1e-12, &log);
+    // A fit can be stored and taken apart later
+    calculate_assign("#fit = linreg([1, 2, 3], [2, 4, 6])", &mut log);
+    assert_within("fit.x", 2.0, 1e-12, &log);
+    assert_within("fit.y", 0.0, 1e-12, &log);
+    assert_eq!(
+        calculate("linreg([1, 1], [1, 2])", &log),
+        CalculatorError::DomainError(String::from("linreg")).to_string()
+    );
+    assert_eq!(
+        calculate("linreg([1, 2, 3], [1, 2])", &log),
+        CalculatorError::ShapeMismatch(3, 2).to_string()
+    );
+
+    // y = 3e^(2x)
+    assert_within("expfit([0, 1, 2], 3e^[0, 2, 4]).x", 3.0, 1e-12, &log);
+    assert_within("expfit([0, 1, 2], 3e^[0, 2, 4]).y", 2.0, 1e-12, &log);
+    assert_within("expfit([0, 1, 2], 3e^[0, 2, 4]).z", 1.0, 1e-12, &log);
+    assert_eq!(
+        calculate("expfit([0, 1], [1, -1])", &log),
+        CalculatorError::DomainError(String::from("expfit")).to_string()
+    );
+    // y = 2ln(x) + 1
+    assert_within("logfit([1, e, e^2], [1, 3, 5]).x", 2.0, 1e-12, &log);
+    assert_within("logfit([1, e, e^2], [1, 3, 5]).y", 1.0, 1e-12, &log);
+    assert_eq!(
+        calculate("logfit([0, 1], [1, 2])", &log),
+        CalculatorError::DomainError(String::from("logfit")).to_string()
+    );
+
+    assert_eq!(calculate("interp([0, 10], [0, 100], 2.5)", &log), " = 25");
+    // The points don't need to be in order
+    assert_eq!(
+        calculate("interp([2, 0, 1], [0, 10, 20], [0, 0.5, 1, 1.5, 2])", &log),
+        " = [10, 15, 20, 10, 0]"
+    );
+    assert_eq!(calculate("interp([3], [7], 3)", &log), " = 7");
+    assert_eq!(
+        calculate("interp([0, 1], [0, 1], 2)", &log),
+        CalculatorError::DomainError(String::from("interp")).to_string()
+    );
+    assert_eq!(
+        calculate("interp([1, 1], [0, 1], 1)", &log),
+        CalculatorError::DomainError(String::from("interp")).to_string()
+    );
+}
+
+#[test]
+fn signal_processing() {
+    let log = Log::new();
+    assert_eq!(calculate("fft([1, 0, 0, 0])", &log), " = [1, 1, 1, 1]");
+    assert_eq!(
+        calculate("fft([1, 2, 3, 4])", &log),
+        " = [10, -2 + 2i, -2, -2 - 2i]"
+    );
+    // Lengths which aren't a power of 2
+    assert_eq!(calculate("fft([1, 1, 1])", &log), " = [3, 0, 0]");
+    // 2cos(pi/3) + 1
+    assert_within("abs(fft([0, 1, 0, -1, 0, 1])).1", 2.0, 1e-12, &log);
+    assert_within(
+        "abs(fft(map(0..9, k -> sin(2pi k / 10)))).1",
+        5.0,
+        1e-12,
+        &log,
+    );
+    assert_within(
+        "abs(fft(map(0..9, k -> sin(2pi k / 10)))).9",
+        5.0,
+        1e-12,
+        &log,
+    );
+    assert_within(
+        "arg(fft(map(0..9, k -> sin(2pi k / 10)))).1",
+        -PI / 2.0,
+        1e-12,
+        &log,
+    );
+    assert_within("ifft(fft([3, 1, 4, 1, 5])).2", 4.0, 1e-12, &log);
+    assert_within("ifft(fft([3, 1, 4, 1, 5])).4", 5.0, 1e-12, &log);
+    assert_eq!(calculate("ifft([4, 0, 0, 0])", &log), " = [1, 1, 1, 1]");
+    assert_eq!(
+        calculate("fft([])", &log),
+        CalculatorError::EmptyInput(String::from("fft")).to_string()
+    );
+
+    assert_eq!(calculate("conv([1, 2], [1, 1])", &log), " = [1, 3, 2]");
+    assert_eq!(calculate("conv([1, 2, 3], 2)", &log), " = [2, 4, 6]");
+    // Long convolutions go through the FFT
+    assert_within(
+        "conv(repeat(1, 4000), repeat(1, 4000)).3999",
+        4000.0,
+        1e-12,
+        &log,
+    );
+    assert_within("conv(repeat(1, 4000), repeat(1, 4000)).-1", 1.0, 1e-9, &log);
+    assert_eq!(calculate("cumsum([1, 2, 3, 4])", &log), " = [1, 3, 6, 10]");
+    assert_eq!(calculate("diff([1, 4, 9, 16])", &log), " = [3, 5, 7]");
+    assert_eq!(calculate("diff(diff([1, 4, 9, 16]))", &log), " = [2, 2]");
+    assert_eq!(
+        calculate("movavg([1, 2, 3, 4, 5], 2)", &log),
+        " = [1.5, 2.5, 3.5, 4.5]"
+    );
+    assert_eq!(
+        calculate("movavg([1, 2], 3)", &log),
+        CalculatorError::DomainError(String::from("movavg")).to_string()
+    );
+    assert_eq!(calculate("dft_freqs(4, 100)", &log), " = [0, 25, -50, -25]");
+    assert_eq!(calculate("dft_freqs(5, 10)", &log), " = [0, 2, 4, -4, -2]");
+    assert_eq!(
+        calculate("dft_freqs(2000000, 10)", &log),
+        CalculatorError::VectorTooLong(1_000_000).to_string()
+    );
+}
+
+#[test]
+fn random_numbers() {
+    let mut log = Log::new();
+    calculate_assign("/seed 42", &mut log);
+    calculate_assign("#a = rand()", &mut log);
+    calculate_assign("#b = rand()", &mut log);
+    assert_ne!(calculate("a - b", &log), " = 0");
+    // The same seed gives the same numbers again
+    calculate_assign("/seed 42", &mut log);
+    calculate_assign("#c = rand()", &mut log);
+    assert_eq!(calculate("a - c", &log), " = 0");
+
+    // The preview doesn't reroll, and shows what enter will give
+    let preview = calculate("rand()", &log);
+    assert_eq!(calculate("rand()", &log), preview);
+    assert_eq!(calculate_assign("rand()", &mut log), preview);
+    assert_ne!(calculate("rand()", &log), preview);
+
+    let draws = |input: &str, log: &Log| match parse(input.chars(), log) {
+        Ok(Vector(v)) => v,
+        other => panic!("{} gave {:?}", input, other),
+    };
+    let uniform = draws("rand(1000)", &log);
+    assert_eq!(uniform.len(), 1000);
+    assert!(uniform.iter().all(|x| (0.0..1.0).contains(x)));
+    let dice = draws("randint(1, 6, 1000)", &log);
+    assert!(dice
+        .iter()
+        .all(|x| x.fract() == 0.0 && (1.0..=6.0).contains(x)));
+    assert!((1..=6).all(|n| dice.contains(&(n as f64))));
+    let normal = draws("randn(10000)", &log);
+    let mean = normal.iter().sum::<f64>() / normal.len() as f64;
+    let variance = normal.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / normal.len() as f64;
+    assert!(mean.abs() < 0.05 && (variance - 1.0).abs() < 0.05);
+    // Lambdas keep drawing new numbers rather than each starting from the same state
+    let mapped = draws("map(1..3, x -> rand())", &log);
+    assert!(mapped[0] != mapped[1] && mapped[1] != mapped[2]);
+
+    assert_eq!(calculate("randint(3, 3)", &log), " = 3");
+    assert_eq!(
+        calculate("randint(6, 1)", &log),
+        CalculatorError::DomainError(String::from("randint")).to_string()
+    );
+    assert_eq!(
+        calculate("rand(2000000)", &log),
+        CalculatorError::VectorTooLong(1_000_000).to_string()
+    );
+    assert_eq!(
+        calculate_assign("/seed x", &mut log),
+        CalculatorError::InvalidCommandArgument(String::from("seed")).to_string()
+    );
+}
+
+#[test]
+fn distributions() {
+    let log = Log::new();
+    assert_close("normpdf(0)", 0.398_942_280_401_432_7, &log);
+    assert_close("normcdf(1.96)", 0.975_002_104_851_779_5, &log);
+    assert_close("normcdf(110, 100, 15)", 0.747_507_462_453_077_1, &log);
+    assert_close("norminv(0.975)", 1.959_963_984_540_054, &log);
+    assert_close("norminv(0.5, 100, 15)", 100.0, &log);
+    assert_within("norminv(normcdf(-8))", -8.0, 1e-9, &log);
+    assert_eq!(calculate("norminv(0)", &log), " = -inf");
+    assert_eq!(
+        calculate("normpdf(0, 0, 0)", &log),
+        CalculatorError::DomainError(String::from("normpdf")).to_string()
+    );
+
+    assert_close("binompdf(3, 10, 0.5)", 0.117_187_5, &log);
+    assert_close("binomcdf(3, 10, 0.5)", 0.171_875, &log);
+    assert_within("binompdf(2, 4, 0.5)", 0.375, 0.0, &log);
+    assert_within("binomcdf(2, 4, 0.5)", 0.6875, 0.0, &log);
+    assert_close("binompdf(1000, 2000, 0.5)", 0.017_839_011_145_854_32, &log);
+    assert_close("binomcdf(1000, 2000, 0.5)", 0.508_919_505_572_927_2, &log);
+    assert_eq!(calculate("binominv(0.5, 10, 0.5)", &log), " = 5");
+    assert_eq!(calculate("binompdf(2.5, 10, 0.5)", &log), " = 0");
+    assert_eq!(calculate("binomcdf(10, 10, 0.3)", &log), " = 1");
+    assert_eq!(
+        calculate("binompdf(1, 10, 2)", &log),
+        CalculatorError::DomainError(String::from("binompdf")).to_string()
+    );
+
+    assert_close("poisspdf(2, 3)", 0.224_041_807_655_387_75, &log);
+    assert_close("poisscdf(2, 3)", 0.423_190_081_126_843_5, &log);
+    assert_eq!(calculate("poissinv(0.5, 3)", &log), " = 3");
+    assert_eq!(calculate("poissinv(0.999, 3)", &log), " = 10");
+
+    assert_eq!(calculate("unifpdf(1, 0, 4)", &log), " = 0.25");
+    assert_eq!(
+        calculate("unifcdf([-1, 0.25, 3], 0, 2)", &log),
+        " = [0, 0.125, 1]"
+    );
+    assert_eq!(calculate("unifinv(0.5, 2, 4)", &log), " = 3");
+
+    assert_eq!(calculate("exppdf(0, 2)", &log), " = 2");
+    assert_close("expcdf(1, 1)", 0.632_120_558_828_557_7, &log);
+    assert_close("expinv(0.5, 1)", std::f64::consts::LN_2, &log);
+
+    // One degree of freedom is the Cauchy distribution
+    assert_close("tpdf(0, 1)", 1.0 / PI, &log);
+    assert_close("tcdf(1, 1)", 0.75, &log);
+    assert_close("tcdf(0, 5)", 0.5, &log);
+    assert_within("tcdf(2.015048373333024, 5)", 0.95, 1e-12, &log);
+    assert_within("tinv(0.975, 10)", 2.228_138_851_986_274, 1e-12, &log);
+    assert_within("tinv(0.025, 10)", -2.228_138_851_986_274, 1e-12, &log);
+    // Close to 0 the result keeps its precision
+    for df in ["1", "2.5", "10", "1000"] {
+        assert_eq!(calculate(&format!("tinv(0.5, {})", df), &log), " = 0");
+    }
+    assert_within("tcdf(1e-9, 1)", 0.5 + 1e-9 / PI, 1e-15, &log);
+    assert_within("tinv(0.5 + 1e-9 / pi, 1) * 1e9", 1.0, 1e-6, &log);
+
+    assert_eq!(
+        calculate("expinv(2, 1)", &log),
+        CalculatorError::DomainError(String::from("expinv")).to_string()
+    );
+    assert_eq!(
+        calculate("tcdf(1)", &log),
+        CalculatorError::MissingFunctionParameters(String::from("tcdf")).to_string()
+    );
+}
@@ -1,46 +1,116 @@
 use crate::calculator::num_types::NumType;
-use crate::calculator::CalculatorError;
+use crate::calculator::number_format::NumberFormat;
+use crate::calculator::{CalculatorError, KEYWORDS};
+pub use angle_mode::AngleMode;
+pub use int_mode::{IntMode, Overflow};
+pub use random::Random;
 use std::collections::HashMap;
 use symbol_type::SymbolType;
 use symbol_type::SymbolType::*;
 
+mod angle_mode;
 mod built_in;
+pub use built_in::{calculus, sequences, special};
+mod int_mode;
+mod random;
 pub mod symbol_type;
 
-type NumFn = fn(Vec<NumType>) -> Result<NumType, CalculatorError>;
+// Built in functions get read access to the log for settings such as the angle mode
+pub type NumFn = fn(Vec<NumType>, &Log) -> Result<NumType, CalculatorError>;
+// Lazy functions get the text of each parameter, so they can evaluate them however they need to
+pub type LazyNumFn = fn(&[String], &Log) -> Result<NumType, CalculatorError>;
+// Commands are given anything typed after their name as an argument
+pub type CommandFn = fn(&mut Log, &str) -> String;
+
+// Most function calls which can be inside one another
+const MAX_DEPTH: usize = 64;
 
 pub struct Log {
     pub history: Vec<(String, String)>,
     pub last_number: NumType,
+    pub angle_mode: AngleMode,
+    pub format: NumberFormat,
+    // Programmer mode is on whenever an integer type is set
+    pub int_mode: Option<IntMode>,
+    // Legacy vector behavior, mismatched lengths are padded with 1s instead of giving an error
+    pub lenient_vectors: bool,
+    // Shared with scoped copies, so drawing a number anywhere moves every copy on
+    pub random: Random,
+    // How many function calls deep this log is, see scope()
+    depth: usize,
     vars: HashMap<String, NumType>,
     consts: HashMap<String, NumType>,
     default_functions: HashMap<String, NumFn>,
-    commands: HashMap<String, fn(&mut Log) -> String>,
+    lazy_functions: HashMap<String, LazyNumFn>,
+    aliases: HashMap<String, String>,
+    commands: HashMap<String, CommandFn>,
 }
 
 impl Default for Log {
     fn default() -> Self {
         Log {
             last_number: NumType::Scalar(1.0),
+            angle_mode: AngleMode::default(),
+            format: NumberFormat::default(),
+            int_mode: None,
+            lenient_vectors: false,
+            random: Random::default(),
+            depth: 0,
             history: Vec::<(String, String)>::default(),
             vars: HashMap::<String, NumType>::default(),
             consts: HashMap::<String, NumType>::default(),
             default_functions: HashMap::<String, NumFn>::default(),
-            commands: HashMap::<String, fn(&mut Log) -> String>::default(),
+            lazy_functions: HashMap::<String, LazyNumFn>::default(),
+            aliases: HashMap::<String, String>::default(),
+            commands: HashMap::<String, CommandFn>::default(),
         }
     }
 }
 
 impl Log {
     pub fn new() -> Self {
+        let consts = built_in::get_constants_hashmap();
+        let default_functions = built_in::get_default_functions_hashmap();
+        let lazy_functions = built_in::get_lazy_functions_hashmap();
         Log {
-            consts: built_in::get_constants_hashmap(),
-            default_functions: built_in::get_default_functions_hashmap(),
+            aliases: built_in::get_aliases_hashmap(
+                consts
+                    .keys()
+                    .chain(default_functions.keys())
+                    .chain(lazy_functions.keys()),
+            ),
+            consts,
+            default_functions,
+            lazy_functions,
             commands: built_in::get_default_commands_hashmap(),
             ..Default::default()
         }
     }
 
+    // A copy of the log for a function to run in, so its parameters don't overwrite any variables
+    // Functions can call themselves, so the depth is limited rather than overflowing the stack
+    pub fn scope(&self) -> Result<Log, CalculatorError> {
+        if self.depth >= MAX_DEPTH {
+            return Err(CalculatorError::RecursionLimit);
+        }
+        Ok(Log {
+            history: Vec::new(),
+            last_number: self.last_number.clone(),
+            angle_mode: self.angle_mode,
+            format: self.format.clone(),
+            int_mode: self.int_mode,
+            lenient_vectors: self.lenient_vectors,
+            random: self.random.clone(),
+            depth: self.depth + 1,
+            vars: self.vars.clone(),
+            consts: self.consts.clone(),
+            default_functions: self.default_functions.clone(),
+            lazy_functions: self.lazy_functions.clone(),
+            aliases: self.aliases.clone(),
+            commands: self.commands.clone(),
+        })
+    }
+
     pub fn push_results(&mut self, input: &str, output: &str) {
         self.history.push((input.to_owned(), output.to_owned()))
     }
@@ -62,16 +132,29 @@ impl Log {
         self.history.clear();
     }
 
+    // Finds the real name of a built in symbol, ignoring case
+    fn resolve_alias<'a>(&'a self, symbol: &'a str) -> &'a str {
+        self.aliases
+            .get(&symbol.to_ascii_lowercase())
+            .map_or(symbol, |s| s.as_str())
+    }
+
     #[allow(clippy::manual_map)]
-    pub fn search_symbol(&self, symbol: &str) -> Option<SymbolType> {
+    pub fn search_symbol(&self, symbol: &str) -> Option<SymbolType<'_>> {
+        // Built in symbols are case insensitive, variables are not
+        let builtin = self.resolve_alias(symbol);
+
         // Try every base of symbols
         // First try consts
-        if let Some(s) = self.consts.get(symbol) {
+        if let Some(s) = self.consts.get(builtin) {
             Some(Variable(s))
         } else if let Some(s) = self.vars.get(symbol) {
             // Then try vars
             Some(Variable(s))
-        } else if let Some(f) = self.default_functions.get(symbol) {
+        } else if let Some(f) = self.lazy_functions.get(builtin) {
+            // Lazy functions come first since some share a name with an ordinary function
+            Some(LazyFn(*f))
+        } else if let Some(f) = self.default_functions.get(builtin) {
             // Then try built in functions
             Some(DefaultFn(*f))
         } else {
@@ -80,7 +163,44 @@ impl Log {
         }
     }
 
-    pub fn search_command(&self, name: &str) -> Option<fn(&mut Log) -> String> {
-        self.commands.get(name).copied()
+    // Finds an ordinary built in function, skipping any lazy function with the same name
+    pub fn search_default_function(&self, name: &str) -> Option<NumFn> {
+        self.default_functions
+            .get(self.resolve_alias(name))
+            .copied()
     }
+
+    // Checks that a name may be used for a variable
+    // Names look like [A-Za-z_][A-Za-z0-9_]* and may not collide with anything built in (in any case),
+    // otherwise the assignment would succeed but the built in symbol would still be found first
+    pub fn validate_name(&self, name: &str) -> Result<(), CalculatorError> {
+        if !is_identifier(name) {
+            Err(CalculatorError::InvalidName(name.to_owned()))
+        } else if self.aliases.contains_key(&name.to_ascii_lowercase())
+            || self.commands.contains_key(&name.to_ascii_lowercase())
+            || KEYWORDS.contains(&name.to_ascii_lowercase().as_str())
+        {
+            Err(CalculatorError::ReservedName(name.to_owned()))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn search_command(&self, name: &str) -> Option<CommandFn> {
+        self.commands.get(&name.to_ascii_lowercase()).copied()
+    }
+}
+
+// Symbol names start with a letter or underscore, followed by any letters, digits or underscores
+pub fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if is_identifier_start(c)) && chars.all(is_identifier_char)
+}
+
+pub fn is_identifier_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+pub fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
 }
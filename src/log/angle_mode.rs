@@ -0,0 +1,51 @@
+use std::{f64::consts::PI, fmt::Display};
+
+// Units used by trigonometric functions for their inputs (and inverse functions for their outputs)
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum AngleMode {
+    #[default]
+    Radians,
+    Degrees,
+    Gradians,
+}
+
+use AngleMode::*;
+
+impl AngleMode {
+    // Converts an angle in this unit to radians
+    pub fn to_radians(self, n: f64) -> f64 {
+        match self {
+            Radians => n,
+            Degrees => n.to_radians(),
+            Gradians => n * PI / 200.0,
+        }
+    }
+
+    // Converts an angle in radians to this unit
+    pub fn radians_to_mode(self, n: f64) -> f64 {
+        match self {
+            Radians => n,
+            Degrees => n.to_degrees(),
+            Gradians => n * 200.0 / PI,
+        }
+    }
+
+    // Converts an angle in degrees to this unit, used by the ° suffix
+    pub fn degrees_to_mode(self, n: f64) -> f64 {
+        match self {
+            Radians => n.to_radians(),
+            Degrees => n,
+            Gradians => n * 10.0 / 9.0,
+        }
+    }
+}
+
+impl Display for AngleMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Radians => write!(f, "RAD"),
+            Degrees => write!(f, "DEG"),
+            Gradians => write!(f, "GRAD"),
+        }
+    }
+}
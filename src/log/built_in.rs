@@ -1,18 +1,32 @@
-use crate::calculator::num_types::NumType;
 use crate::calculator::num_types::NumType::*;
-use crate::calculator::CalculatorError;
+use crate::calculator::num_types::{self, to_exact_integer, to_integer, NumType};
+use crate::calculator::number_format::Notation;
+use crate::calculator::{evaluate, CalculatorError};
 use std::collections::HashMap;
 use std::f64::consts::{E, PI, TAU};
 
-use super::Log;
+use super::{AngleMode, CommandFn, IntMode, LazyNumFn, Log, NumFn, Overflow};
 
-type NumFn = fn(Vec<NumType>) -> Result<NumType, CalculatorError>;
+pub mod calculus;
+mod distributions;
+mod geometry;
+mod higher_order;
+mod number_theory;
+mod ode;
+mod polynomial;
+mod regression;
+pub mod sequences;
+mod series;
+mod signal;
+pub mod special;
+mod statistics;
 
 pub fn get_constants_hashmap() -> HashMap<String, NumType> {
     let mut c: HashMap<String, NumType> = HashMap::new();
     c.insert(String::from("pi"), Scalar(PI));
     c.insert(String::from("e"), Scalar(E));
     c.insert(String::from("tau"), Scalar(TAU));
+    c.insert(String::from("inf"), Scalar(f64::INFINITY));
 
     c
 }
@@ -27,65 +41,69 @@ pub fn get_default_functions_hashmap() -> HashMap<String, NumFn> {
     The actual function happens in the body of the if let statement
     Else, it will return a missing parameters error
     */
-    f.insert(String::from("sin"), |v| {
+    f.insert(String::from("sin"), |v, l| {
         if let Some(Scalar(n)) = v.first() {
-            Ok(Scalar(n.sin()))
+            Ok(Scalar(l.angle_mode.to_radians(*n).sin()))
         } else {
             Err(CalculatorError::MissingFunctionParameters(String::from(
                 "sin",
             )))
         }
     });
-    f.insert(String::from("cos"), |v| {
+    f.insert(String::from("cos"), |v, l| {
         if let Some(Scalar(n)) = v.first() {
-            Ok(Scalar(n.cos()))
+            Ok(Scalar(l.angle_mode.to_radians(*n).cos()))
         } else {
             Err(CalculatorError::MissingFunctionParameters(String::from(
                 "cos",
             )))
         }
     });
-    f.insert(String::from("tan"), |v| {
+    f.insert(String::from("tan"), |v, l| {
         if let Some(Scalar(n)) = v.first() {
-            Ok(Scalar(n.tan()))
+            Ok(Scalar(l.angle_mode.to_radians(*n).tan()))
         } else {
             Err(CalculatorError::MissingFunctionParameters(String::from(
                 "tan",
             )))
         }
     });
-    f.insert(String::from("asin"), |v| {
+    f.insert(String::from("asin"), |v, l| {
         if let Some(Scalar(n)) = v.first() {
-            Ok(Scalar(n.asin()))
+            Ok(Scalar(l.angle_mode.radians_to_mode(n.asin())))
         } else {
             Err(CalculatorError::MissingFunctionParameters(String::from(
                 "asin",
             )))
         }
     });
-    f.insert(String::from("acos"), |v| {
+    f.insert(String::from("acos"), |v, l| {
         if let Some(Scalar(n)) = v.first() {
-            Ok(Scalar(n.acos()))
+            Ok(Scalar(l.angle_mode.radians_to_mode(n.acos())))
         } else {
             Err(CalculatorError::MissingFunctionParameters(String::from(
                 "acos",
             )))
         }
     });
-    f.insert(String::from("atan"), |v| {
+    f.insert(String::from("atan"), |v, l| {
         if let Some(Scalar(n)) = v.first() {
-            Ok(Scalar(n.atan()))
+            Ok(Scalar(l.angle_mode.radians_to_mode(n.atan())))
         } else {
             Err(CalculatorError::MissingFunctionParameters(String::from(
                 "atan",
             )))
         }
     });
-    f.insert(String::from("abs"), |v| {
+    f.insert(String::from("abs"), |v, _| {
         if let Some(n) = v.first() {
             match n {
                 Scalar(s) => Ok(Scalar(s.abs())),
+                Integer(i, _) => Ok(Scalar(i.abs() as f64)),
                 Vector(v) => Ok(Vector(v.iter().map(|f| f.abs()).collect())),
+                // The magnitude of complex numbers
+                Complex(v) => Ok(Vector(v.iter().map(|z| z.abs()).collect())),
+                Function(_) => Err(CalculatorError::FunctionAsNumber),
             }
         } else {
             Err(CalculatorError::MissingFunctionParameters(String::from(
@@ -93,11 +111,12 @@ pub fn get_default_functions_hashmap() -> HashMap<String, NumFn> {
             )))
         }
     });
-    f.insert(String::from("round"), |v| {
+    f.insert(String::from("round"), |v, _| {
         if let Some(n) = v.first() {
             match n {
                 Scalar(s) => Ok(Scalar(s.round())),
                 Vector(v) => Ok(Vector(v.iter().map(|f| f.round()).collect())),
+                other => Err(other.not_real()),
             }
         } else {
             Err(CalculatorError::MissingFunctionParameters(String::from(
@@ -105,11 +124,12 @@ pub fn get_default_functions_hashmap() -> HashMap<String, NumFn> {
             )))
         }
     });
-    f.insert(String::from("ceil"), |v| {
+    f.insert(String::from("ceil"), |v, _| {
         if let Some(n) = v.first() {
             match n {
                 Scalar(s) => Ok(Scalar(s.ceil())),
                 Vector(v) => Ok(Vector(v.iter().map(|f| f.ceil()).collect())),
+                other => Err(other.not_real()),
             }
         } else {
             Err(CalculatorError::MissingFunctionParameters(String::from(
@@ -117,11 +137,12 @@ pub fn get_default_functions_hashmap() -> HashMap<String, NumFn> {
             )))
         }
     });
-    f.insert(String::from("floor"), |v| {
+    f.insert(String::from("floor"), |v, _| {
         if let Some(n) = v.first() {
             match n {
                 Scalar(s) => Ok(Scalar(s.floor())),
                 Vector(v) => Ok(Vector(v.iter().map(|f| f.floor()).collect())),
+                other => Err(other.not_real()),
             }
         } else {
             Err(CalculatorError::MissingFunctionParameters(String::from(
@@ -129,7 +150,34 @@ pub fn get_default_functions_hashmap() -> HashMap<String, NumFn> {
             )))
         }
     });
-    f.insert(String::from("log"), |v| {
+    f.insert(String::from("mod"), |v, _| {
+        if let Some([n, d]) = v.get(0..=1) {
+            n.clone().modulo(d)
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "mod",
+            )))
+        }
+    });
+    f.insert(String::from("rem"), |v, _| {
+        if let Some([n, d]) = v.get(0..=1) {
+            n.clone().rem(d)
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "rem",
+            )))
+        }
+    });
+    f.insert(String::from("rem_euclid"), |v, _| {
+        if let Some([n, d]) = v.get(0..=1) {
+            n.clone().rem_euclid(d)
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "rem_euclid",
+            )))
+        }
+    });
+    f.insert(String::from("log"), |v, _| {
         if let Some([Scalar(s), Scalar(b)]) = v.get(0..=1) {
             Ok(Scalar(s.log(*b)))
         } else {
@@ -138,7 +186,7 @@ pub fn get_default_functions_hashmap() -> HashMap<String, NumFn> {
             )))
         }
     });
-    f.insert(String::from("ln"), |v| {
+    f.insert(String::from("ln"), |v, _| {
         if let Some(Scalar(s)) = v.first() {
             Ok(Scalar(s.ln()))
         } else {
@@ -147,7 +195,7 @@ pub fn get_default_functions_hashmap() -> HashMap<String, NumFn> {
             )))
         }
     });
-    f.insert(String::from("sqrt"), |v| {
+    f.insert(String::from("sqrt"), |v, _| {
         if let Some(Scalar(s)) = v.first() {
             Ok(Scalar(s.sqrt()))
         } else {
@@ -156,7 +204,7 @@ pub fn get_default_functions_hashmap() -> HashMap<String, NumFn> {
             )))
         }
     });
-    f.insert(String::from("rad"), |v| {
+    f.insert(String::from("rad"), |v, _| {
         if let Some(Scalar(s)) = v.first() {
             Ok(Scalar(s.to_radians()))
         } else {
@@ -165,7 +213,7 @@ pub fn get_default_functions_hashmap() -> HashMap<String, NumFn> {
             )))
         }
     });
-    f.insert(String::from("deg"), |v| {
+    f.insert(String::from("deg"), |v, _| {
         if let Some(Scalar(s)) = v.first() {
             Ok(Scalar(s.to_degrees()))
         } else {
@@ -175,36 +223,1168 @@ pub fn get_default_functions_hashmap() -> HashMap<String, NumFn> {
         }
     });
 
+    // Special functions, single input ones work on vectors component by component
+    f.insert(String::from("factorial"), |v, _| {
+        if let Some(n) = v.first() {
+            n.clone().try_map(special::factorial)
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "factorial",
+            )))
+        }
+    });
+    f.insert(String::from("gamma"), |v, _| {
+        if let Some(n) = v.first() {
+            n.clone().try_map(special::gamma)
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "gamma",
+            )))
+        }
+    });
+    f.insert(String::from("lgamma"), |v, _| {
+        if let Some(n) = v.first() {
+            n.clone().try_map(special::lgamma)
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "lgamma",
+            )))
+        }
+    });
+    f.insert(String::from("beta"), |v, _| {
+        if let Some([Scalar(a), Scalar(b)]) = v.get(0..=1) {
+            Ok(Scalar(special::beta(*a, *b)?))
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "beta",
+            )))
+        }
+    });
+    f.insert(String::from("erf"), |v, _| {
+        if let Some(n) = v.first() {
+            n.clone().try_map(special::erf)
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "erf",
+            )))
+        }
+    });
+    f.insert(String::from("erfc"), |v, _| {
+        if let Some(n) = v.first() {
+            n.clone().try_map(special::erfc)
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "erfc",
+            )))
+        }
+    });
+    // Regularized lower and upper incomplete gamma functions
+    f.insert(String::from("gammainc"), |v, _| {
+        if let Some([Scalar(a), Scalar(b)]) = v.get(0..=1) {
+            Ok(Scalar(special::gamma_p(*a, *b)?))
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "gammainc",
+            )))
+        }
+    });
+    f.insert(String::from("gammaincc"), |v, _| {
+        if let Some([Scalar(a), Scalar(b)]) = v.get(0..=1) {
+            Ok(Scalar(special::gamma_q(*a, *b)?))
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "gammaincc",
+            )))
+        }
+    });
+
+    // Combinatorics and number theory, these only take whole numbers
+    // gcd and lcm take any number of parameters, the signs don't matter so they work on the sizes
+    f.insert(String::from("gcd"), |v, _| {
+        let n = integer_params(&v, v.len().max(1), "gcd")?;
+        Ok(Scalar(
+            n.into_iter()
+                .map(i64::unsigned_abs)
+                .fold(0, number_theory::gcd) as f64,
+        ))
+    });
+    f.insert(String::from("lcm"), |v, _| {
+        let n = integer_params(&v, v.len().max(1), "lcm")?;
+        exact_result(
+            n.into_iter()
+                .map(i64::unsigned_abs)
+                .try_fold(1, number_theory::lcm)?,
+            "lcm",
+        )
+    });
+    f.insert(String::from("ncr"), |v, _| {
+        let n = integer_params(&v, 2, "ncr")?;
+        Ok(Scalar(number_theory::ncr(n[0], n[1])?))
+    });
+    f.insert(String::from("npr"), |v, _| {
+        let n = integer_params(&v, 2, "npr")?;
+        Ok(Scalar(number_theory::npr(n[0], n[1])?))
+    });
+    f.insert(String::from("isprime"), |v, _| {
+        let n = integer_params(&v, 1, "isprime")?;
+        Ok(Scalar(
+            if n[0] >= 0 && number_theory::is_prime(n[0] as u64) {
+                1.0
+            } else {
+                0.0
+            },
+        ))
+    });
+    f.insert(String::from("nextprime"), |v, _| {
+        let n = integer_params(&v, 1, "nextprime")?;
+        exact_result(number_theory::next_prime(n[0]) as u64, "nextprime")
+    });
+    f.insert(String::from("factor"), |v, _| {
+        let n = integer_params(&v, 1, "factor")?;
+        Ok(Vector(
+            number_theory::factor(n[0])?
+                .into_iter()
+                .map(|p| p as f64)
+                .collect(),
+        ))
+    });
+    f.insert(String::from("totient"), |v, _| {
+        let n = integer_params(&v, 1, "totient")?;
+        Ok(Scalar(number_theory::totient(n[0])? as f64))
+    });
+    f.insert(String::from("modpow"), |v, _| {
+        let n = integer_params(&v, 3, "modpow")?;
+        Ok(Scalar(number_theory::mod_pow(n[0], n[1], n[2])? as f64))
+    });
+    f.insert(String::from("modinv"), |v, _| {
+        let n = integer_params(&v, 2, "modinv")?;
+        Ok(Scalar(number_theory::mod_inv(n[0], n[1])? as f64))
+    });
+
+    // Sequence functions
+    // linspace(a, b, n) is n evenly spaced numbers from a to b
+    f.insert(String::from("linspace"), |v, _| {
+        if let Some([Scalar(a), Scalar(b), Scalar(n)]) = v.get(0..3) {
+            let n = to_integer(*n, "linspace")?;
+            Ok(Vector(sequences::linspace(*a, *b, n)?))
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "linspace",
+            )))
+        }
+    });
+    // repeat(x, n) repeats a number or a whole vector
+    f.insert(String::from("repeat"), |v, _| {
+        if let Some([x, Scalar(n)]) = v.get(0..2) {
+            let n = to_integer(*n, "repeat")?;
+            let values = match x {
+                Scalar(s) => vec![*s],
+                Vector(v) => v.clone(),
+                other => return Err(other.not_real()),
+            };
+            Ok(Vector(sequences::repeat(&values, n)?))
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "repeat",
+            )))
+        }
+    });
+    // Joins any amount of vectors and numbers into one vector
+    f.insert(String::from("concat"), |v, _| {
+        let mut joined = Vec::new();
+        for n in v {
+            match n {
+                Scalar(s) => joined.push(s),
+                Vector(v) => joined.extend(v),
+                other => return Err(other.not_real()),
+            }
+        }
+        if joined.len() > sequences::MAX_LEN {
+            return Err(CalculatorError::VectorTooLong(sequences::MAX_LEN));
+        }
+        Ok(Vector(joined))
+    });
+
+    // Statistics functions take either one vector or any amount of numbers
+    // var and stdev are for samples, varp and stdevp for whole populations
+    f.insert(String::from("sum"), |v, _| {
+        let data = data_params(&v, "sum")?;
+        Ok(Scalar(statistics::sum(&data)))
+    });
+    f.insert(String::from("prod"), |v, _| {
+        let data = data_params(&v, "prod")?;
+        Ok(Scalar(statistics::product(&data)))
+    });
+    f.insert(String::from("mean"), |v, _| {
+        let data = data_params(&v, "mean")?;
+        Ok(Scalar(statistics::mean(&data)))
+    });
+    f.insert(String::from("median"), |v, _| {
+        let data = data_params(&v, "median")?;
+        Ok(Scalar(statistics::median(&data)))
+    });
+    f.insert(String::from("mode"), |v, _| {
+        let data = data_params(&v, "mode")?;
+        Ok(Scalar(statistics::mode(&data)))
+    });
+    f.insert(String::from("min"), |v, _| {
+        let data = data_params(&v, "min")?;
+        Ok(Scalar(statistics::min(&data)))
+    });
+    f.insert(String::from("max"), |v, _| {
+        let data = data_params(&v, "max")?;
+        Ok(Scalar(statistics::max(&data)))
+    });
+    f.insert(String::from("range"), |v, _| {
+        let data = data_params(&v, "range")?;
+        Ok(Scalar(statistics::max(&data) - statistics::min(&data)))
+    });
+    f.insert(String::from("var"), |v, _| {
+        let data = data_params(&v, "var")?;
+        Ok(Scalar(statistics::variance(&data, true, "var")?))
+    });
+    f.insert(String::from("varp"), |v, _| {
+        let data = data_params(&v, "varp")?;
+        Ok(Scalar(statistics::variance(&data, false, "varp")?))
+    });
+    f.insert(String::from("stdev"), |v, _| {
+        let data = data_params(&v, "stdev")?;
+        Ok(Scalar(statistics::variance(&data, true, "stdev")?.sqrt()))
+    });
+    f.insert(String::from("stdevp"), |v, _| {
+        let data = data_params(&v, "stdevp")?;
+        Ok(Scalar(statistics::variance(&data, false, "stdevp")?.sqrt()))
+    });
+    // percentile(data, p) with p from 0 to 100
+    f.insert(String::from("percentile"), |v, _| {
+        if let Some([data, Scalar(p)]) = v.get(0..=1) {
+            let data = data_params(std::slice::from_ref(data), "percentile")?;
+            Ok(Scalar(statistics::percentile(&data, *p)?))
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "percentile",
+            )))
+        }
+    });
+    // zscore(x, data) gives the z-score of x, zscore(data) gives the z-score of every value
+    f.insert(String::from("zscore"), |v, _| match v.get(0..=1) {
+        Some([Scalar(x), data @ Vector(_)]) => {
+            let data = data_params(std::slice::from_ref(data), "zscore")?;
+            Ok(Scalar(statistics::zscore(*x, &data)?))
+        }
+        _ => {
+            let data = data_params(&v, "zscore")?;
+            Ok(Vector(
+                data.iter()
+                    .map(|x| statistics::zscore(*x, &data))
+                    .collect::<Result<_, _>>()?,
+            ))
+        }
+    });
+
+    // Higher order functions take another function, either a lambda or the name of a built in function
+    f.insert(String::from("map"), |v, l| {
+        if let Some([values, Function(func)]) = v.get(0..2) {
+            let values = components(values, "map")?;
+            Ok(Vector(higher_order::map(&values, func, l)?))
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "map",
+            )))
+        }
+    });
+    f.insert(String::from("filter"), |v, l| {
+        if let Some([values, Function(func)]) = v.get(0..2) {
+            let values = components(values, "filter")?;
+            Ok(Vector(higher_order::filter(&values, func, l)?))
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "filter",
+            )))
+        }
+    });
+    f.insert(String::from("reduce"), |v, l| {
+        if let Some([values, Function(func)]) = v.get(0..2) {
+            let values = components(values, "reduce")?;
+            higher_order::reduce(&values, func, l)
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "reduce",
+            )))
+        }
+    });
+    f.insert(String::from("fold"), |v, l| {
+        if let Some([values, init, Function(func)]) = v.get(0..3) {
+            let values = components(values, "fold")?;
+            higher_order::fold(&values, init.clone(), func, l)
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "fold",
+            )))
+        }
+    });
+    f.insert(String::from("zip"), |v, l| {
+        if let Some([a, b, Function(func)]) = v.get(0..3) {
+            let (a, b) = (components(a, "zip")?, components(b, "zip")?);
+            Ok(Vector(higher_order::zip(&a, &b, func, l)?))
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "zip",
+            )))
+        }
+    });
+    f.insert(String::from("sort_by"), |v, l| {
+        if let Some([values, Function(func)]) = v.get(0..2) {
+            let values = components(values, "sort_by")?;
+            Ok(Vector(higher_order::sort_by(&values, func, l)?))
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "sort_by",
+            )))
+        }
+    });
+
+    // Random numbers, rand(n), randint(a, b, n) and randn(n) give vectors of n of them
+    f.insert(String::from("rand"), |v, l| {
+        random_draws(v.first(), "rand", || l.random.next_f64())
+    });
+    // randint(a, b) is a whole number from a to b, including both
+    f.insert(String::from("randint"), |v, l| {
+        let [a, b] = integer_params(&v, 2, "randint")?[..] else {
+            unreachable!("integer_params gives exactly the count asked for")
+        };
+        // Past 2^53 not every whole number is an f64, so they wouldn't all be equally likely
+        let span = b as i128 - a as i128 + 1;
+        if !(1..=1 << 53).contains(&span) {
+            return Err(CalculatorError::DomainError(String::from("randint")));
+        }
+        random_draws(v.get(2), "randint", || {
+            (a as i128 + l.random.below(span as u64) as i128) as f64
+        })
+    });
+    // randn() is normally distributed with a mean of 0 and standard deviation of 1
+    f.insert(String::from("randn"), |v, l| {
+        random_draws(v.first(), "randn", || l.random.normal())
+    });
+
+    // Probability distributions, the first parameter can be a number or a vector
+    // normal takes a mean and standard deviation, which are 0 and 1 if left out
+    f.insert(String::from("normpdf"), |v, _| {
+        distribution(&v, &[Some(0.0), Some(1.0)], "normpdf", |x, p| {
+            distributions::normal_pdf(x, p[0], p[1])
+        })
+    });
+    f.insert(String::from("normcdf"), |v, _| {
+        distribution(&v, &[Some(0.0), Some(1.0)], "normcdf", |x, p| {
+            distributions::normal_cdf(x, p[0], p[1])
+        })
+    });
+    f.insert(String::from("norminv"), |v, _| {
+        distribution(&v, &[Some(0.0), Some(1.0)], "norminv", |q, p| {
+            distributions::normal_inv(q, p[0], p[1])
+        })
+    });
+    // binomial takes the number of trials and the chance of success
+    f.insert(String::from("binompdf"), |v, _| {
+        distribution(&v, &[None; 2], "binompdf", |k, p| {
+            distributions::binomial_pdf(k, p[0], p[1])
+        })
+    });
+    f.insert(String::from("binomcdf"), |v, _| {
+        distribution(&v, &[None; 2], "binomcdf", |k, p| {
+            distributions::binomial_cdf(k, p[0], p[1])
+        })
+    });
+    f.insert(String::from("binominv"), |v, _| {
+        distribution(&v, &[None; 2], "binominv", |q, p| {
+            distributions::binomial_inv(q, p[0], p[1])
+        })
+    });
+    // Poisson takes the mean
+    f.insert(String::from("poisspdf"), |v, _| {
+        distribution(&v, &[None], "poisspdf", |k, p| {
+            distributions::poisson_pdf(k, p[0])
+        })
+    });
+    f.insert(String::from("poisscdf"), |v, _| {
+        distribution(&v, &[None], "poisscdf", |k, p| {
+            distributions::poisson_cdf(k, p[0])
+        })
+    });
+    f.insert(String::from("poissinv"), |v, _| {
+        distribution(&v, &[None], "poissinv", |q, p| {
+            distributions::poisson_inv(q, p[0])
+        })
+    });
+    // uniform takes the lowest and highest values
+    f.insert(String::from("unifpdf"), |v, _| {
+        distribution(&v, &[None; 2], "unifpdf", |x, p| {
+            distributions::uniform_pdf(x, p[0], p[1])
+        })
+    });
+    f.insert(String::from("unifcdf"), |v, _| {
+        distribution(&v, &[None; 2], "unifcdf", |x, p| {
+            distributions::uniform_cdf(x, p[0], p[1])
+        })
+    });
+    f.insert(String::from("unifinv"), |v, _| {
+        distribution(&v, &[None; 2], "unifinv", |q, p| {
+            distributions::uniform_inv(q, p[0], p[1])
+        })
+    });
+    // exponential takes the rate
+    f.insert(String::from("exppdf"), |v, _| {
+        distribution(&v, &[None], "exppdf", |x, p| {
+            distributions::exponential_pdf(x, p[0])
+        })
+    });
+    f.insert(String::from("expcdf"), |v, _| {
+        distribution(&v, &[None], "expcdf", |x, p| {
+            distributions::exponential_cdf(x, p[0])
+        })
+    });
+    f.insert(String::from("expinv"), |v, _| {
+        distribution(&v, &[None], "expinv", |q, p| {
+            distributions::exponential_inv(q, p[0])
+        })
+    });
+    // Student's t takes the degrees of freedom
+    f.insert(String::from("tpdf"), |v, _| {
+        distribution(&v, &[None], "tpdf", |x, p| distributions::t_pdf(x, p[0]))
+    });
+    f.insert(String::from("tcdf"), |v, _| {
+        distribution(&v, &[None], "tcdf", |x, p| distributions::t_cdf(x, p[0]))
+    });
+    f.insert(String::from("tinv"), |v, _| {
+        distribution(&v, &[None], "tinv", |q, p| distributions::t_inv(q, p[0]))
+    });
+
     // Vector functions start here
-    f.insert(String::from("mag"), |v| {
+    f.insert(String::from("mag"), |v, _| {
         if let Some(Vector(v)) = v.first() {
-            Ok(Scalar(
-                v.iter().fold(0.0, |acc, n| acc + n.powf(2.0)).sqrt(),
-            ))
+            Ok(Scalar(geometry::magnitude(v)))
         } else {
             Err(CalculatorError::MissingFunctionParameters(String::from(
                 "mag",
             )))
         }
     });
+    f.insert(String::from("normalize"), |v, _| {
+        if let Some(Vector(v)) = v.first() {
+            Ok(Vector(geometry::normalize(v)?))
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "normalize",
+            )))
+        }
+    });
+    f.insert(String::from("dot"), |v, _| {
+        if let Some([Vector(a), Vector(b)]) = v.get(0..=1) {
+            Ok(Scalar(geometry::dot(a, b)?))
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "dot",
+            )))
+        }
+    });
+    f.insert(String::from("cross"), |v, _| {
+        if let Some([Vector(a), Vector(b)]) = v.get(0..=1) {
+            Ok(Vector(geometry::cross(a, b)?))
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "cross",
+            )))
+        }
+    });
+    f.insert(String::from("angle"), |v, l| {
+        if let Some([Vector(a), Vector(b)]) = v.get(0..=1) {
+            Ok(Scalar(l.angle_mode.radians_to_mode(geometry::angle(a, b)?)))
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "angle",
+            )))
+        }
+    });
+    f.insert(String::from("proj"), |v, _| {
+        if let Some([Vector(a), Vector(b)]) = v.get(0..=1) {
+            Ok(Vector(geometry::project(a, b)?))
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "proj",
+            )))
+        }
+    });
+    f.insert(String::from("reject"), |v, _| {
+        if let Some([Vector(a), Vector(b)]) = v.get(0..=1) {
+            Ok(Vector(geometry::reject(a, b)?))
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "reject",
+            )))
+        }
+    });
+    f.insert(String::from("reflect"), |v, _| {
+        if let Some([Vector(a), Vector(b)]) = v.get(0..=1) {
+            Ok(Vector(geometry::reflect(a, b)?))
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "reflect",
+            )))
+        }
+    });
+    f.insert(String::from("dist"), |v, _| {
+        if let Some([Vector(a), Vector(b)]) = v.get(0..=1) {
+            Ok(Scalar(geometry::distance(a, b)?))
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "dist",
+            )))
+        }
+    });
+
+    // Polynomials are vectors of coefficients, from the highest power down
+    f.insert(String::from("polyval"), |v, _| {
+        if let Some([Vector(p), x]) = v.get(0..2) {
+            x.clone().try_map(|x| Ok(polynomial::eval(p, x)))
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "polyval",
+            )))
+        }
+    });
+    // Real roots are given as a vector, if any are complex they all are
+    f.insert(String::from("polyroots"), |v, _| {
+        if let Some(Vector(p)) = v.first() {
+            let roots = polynomial::roots(p)?;
+            if roots.iter().all(|z| z.im == 0.0) {
+                Ok(Vector(roots.iter().map(|z| z.re).collect()))
+            } else {
+                Ok(Complex(roots))
+            }
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "polyroots",
+            )))
+        }
+    });
+    f.insert(String::from("polyder"), |v, _| {
+        if let Some(Vector(p)) = v.first() {
+            Ok(Vector(polynomial::derivative(p)))
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "polyder",
+            )))
+        }
+    });
+    // polyint(p, c) is the integral which is c at 0, c defaults to 0
+    f.insert(String::from("polyint"), |v, _| match v.as_slice() {
+        [Vector(p)] => Ok(Vector(polynomial::integral(p, 0.0))),
+        [Vector(p), Scalar(c), ..] => Ok(Vector(polynomial::integral(p, *c))),
+        _ => Err(CalculatorError::MissingFunctionParameters(String::from(
+            "polyint",
+        ))),
+    });
+    f.insert(String::from("polymul"), |v, _| {
+        if let Some([Vector(p), Vector(q)]) = v.get(0..2) {
+            Ok(Vector(polynomial::multiply(p, q)))
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "polymul",
+            )))
+        }
+    });
+    // polyfit(xs, ys, degree) is the least squares polynomial through the points
+    f.insert(String::from("polyfit"), |v, _| {
+        if let Some([Vector(xs), Vector(ys), Scalar(degree)]) = v.get(0..3) {
+            let degree = to_integer(*degree, "polyfit")?;
+            if degree < 0 {
+                return Err(CalculatorError::DomainError(String::from("polyfit")));
+            }
+            Ok(Vector(polynomial::fit(xs, ys, degree as usize)?))
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "polyfit",
+            )))
+        }
+    });
+
+    // Fits give their parameters and r² as a vector, so fit.x, fit.y and fit.z pick them out
+    f.insert(String::from("linreg"), |v, _| {
+        if let Some([Vector(xs), Vector(ys)]) = v.get(0..2) {
+            Ok(Vector(regression::linear(xs, ys, "linreg")?))
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "linreg",
+            )))
+        }
+    });
+    f.insert(String::from("expfit"), |v, _| {
+        if let Some([Vector(xs), Vector(ys)]) = v.get(0..2) {
+            Ok(Vector(regression::exponential(xs, ys)?))
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "expfit",
+            )))
+        }
+    });
+    f.insert(String::from("logfit"), |v, _| {
+        if let Some([Vector(xs), Vector(ys)]) = v.get(0..2) {
+            Ok(Vector(regression::logarithmic(xs, ys)?))
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "logfit",
+            )))
+        }
+    });
+    // interp(xs, ys, x) reads between the points at a number or every number in a vector
+    f.insert(String::from("interp"), |v, _| {
+        if let Some([Vector(xs), Vector(ys), x]) = v.get(0..3) {
+            let points = regression::sorted_points(xs, ys)?;
+            x.clone().try_map(|x| regression::interpolate(&points, x))
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "interp",
+            )))
+        }
+    });
+
+    // Complex numbers are taken apart into vectors of real numbers, real numbers work too
+    f.insert(String::from("re"), |v, _| match v.first() {
+        Some(Complex(v)) => Ok(Vector(v.iter().map(|z| z.re).collect())),
+        Some(n @ (Scalar(_) | Vector(_))) => Ok(n.clone()),
+        _ => Err(CalculatorError::MissingFunctionParameters(String::from(
+            "re",
+        ))),
+    });
+    f.insert(String::from("im"), |v, _| match v.first() {
+        Some(Complex(v)) => Ok(Vector(v.iter().map(|z| z.im).collect())),
+        Some(n @ (Scalar(_) | Vector(_))) => n.clone().try_map(|_| Ok(0.0)),
+        _ => Err(CalculatorError::MissingFunctionParameters(String::from(
+            "im",
+        ))),
+    });
+    // The angle of each complex number from the positive real axis, in the current angle mode
+    f.insert(String::from("arg"), |v, l| match v.first() {
+        Some(Complex(v)) => Ok(Vector(
+            v.iter()
+                .map(|z| l.angle_mode.radians_to_mode(z.arg()))
+                .collect(),
+        )),
+        Some(n @ (Scalar(_) | Vector(_))) => n
+            .clone()
+            .try_map(|x| Ok(l.angle_mode.radians_to_mode(0f64.atan2(x)))),
+        _ => Err(CalculatorError::MissingFunctionParameters(String::from(
+            "arg",
+        ))),
+    });
+
+    // Signal processing, fft gives complex values so abs and arg give the magnitude and phase
+    f.insert(String::from("fft"), |v, _| {
+        let x = complex_params(&v, "fft")?;
+        Ok(Complex(signal::fft(&x, false)))
+    });
+    // ifft undoes fft, giving real numbers when every imaginary part is 0
+    f.insert(String::from("ifft"), |v, _| {
+        let x = signal::fft(&complex_params(&v, "ifft")?, true);
+        if x.iter().all(|z| z.im == 0.0) {
+            Ok(Vector(x.iter().map(|z| z.re).collect()))
+        } else {
+            Ok(Complex(x))
+        }
+    });
+    f.insert(String::from("conv"), |v, _| {
+        if let Some([a, b]) = v.get(0..2) {
+            Ok(Vector(signal::convolve(
+                &components(a, "conv")?,
+                &components(b, "conv")?,
+            )))
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "conv",
+            )))
+        }
+    });
+    f.insert(String::from("cumsum"), |v, _| match v.first() {
+        Some(n) => Ok(Vector(signal::cumulative_sum(&components(n, "cumsum")?))),
+        None => Err(CalculatorError::MissingFunctionParameters(String::from(
+            "cumsum",
+        ))),
+    });
+    f.insert(String::from("diff"), |v, _| match v.first() {
+        Some(n) => Ok(Vector(signal::differences(&components(n, "diff")?))),
+        None => Err(CalculatorError::MissingFunctionParameters(String::from(
+            "diff",
+        ))),
+    });
+    // movavg(v, width) averages every width values in a row
+    f.insert(String::from("movavg"), |v, _| {
+        if let Some([n, Scalar(width)]) = v.get(0..2) {
+            let width = to_integer(*width, "movavg")?;
+            if width < 1 {
+                return Err(CalculatorError::DomainError(String::from("movavg")));
+            }
+            Ok(Vector(signal::moving_average(
+                &components(n, "movavg")?,
+                width as usize,
+            )?))
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "movavg",
+            )))
+        }
+    });
+    // dft_freqs(n, fs) is the frequency of each value of fft for n samples at a sample rate of fs
+    f.insert(String::from("dft_freqs"), |v, _| {
+        if let Some([Scalar(n), Scalar(fs)]) = v.get(0..2) {
+            let n = to_integer(*n, "dft_freqs")?;
+            if n < 1 {
+                return Err(CalculatorError::DomainError(String::from("dft_freqs")));
+            }
+            if n as usize > sequences::MAX_LEN {
+                return Err(CalculatorError::VectorTooLong(sequences::MAX_LEN));
+            }
+            Ok(Vector(signal::frequencies(n as usize, *fs)))
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "dft_freqs",
+            )))
+        }
+    });
 
     f
 }
 
-pub fn get_default_commands_hashmap() -> HashMap<String, fn(&mut Log) -> String> {
-    let mut c: HashMap<String, fn(&mut Log) -> String> = HashMap::new();
-    c.insert(String::from("clear"), |l| {
+pub fn get_lazy_functions_hashmap() -> HashMap<String, LazyNumFn> {
+    let mut f: HashMap<String, LazyNumFn> = HashMap::new();
+
+    // sum(k, a, b, expression) adds up the expression for every whole k from a to b
+    // Anything else is the ordinary sum of the parameters
+    f.insert(String::from("sum"), |args, l| {
+        match series::params(args, l, "sum")? {
+            Some(s) => series::fold(s, l, Scalar(0.0), "sum", |a, b| a + b),
+            None => call_eagerly(args, l, "sum"),
+        }
+    });
+    f.insert(String::from("prod"), |args, l| {
+        match series::params(args, l, "prod")? {
+            Some(s) => series::fold(s, l, Scalar(1.0), "prod", |a, b| a * b),
+            None => call_eagerly(args, l, "prod"),
+        }
+    });
+
+    // deriv(expression, x, at, order) is the derivative of the expression with respect to x
+    f.insert(String::from("deriv"), |args, l| {
+        derivative_params(args, l, "deriv", |e| e.accurate("deriv"))
+    });
+    // The estimated error of deriv with the same parameters
+    f.insert(String::from("deriv_err"), |args, l| {
+        derivative_params(args, l, "deriv_err", |e| Ok(e.error))
+    });
+
+    // integrate(expression, x, a, b) is the definite integral from a to b, which may be infinite
+    f.insert(String::from("integrate"), |args, l| {
+        if let [body, var, a, b] = args {
+            let (a, b) = (
+                scalar_param(a, l, "integrate")?,
+                scalar_param(b, l, "integrate")?,
+            );
+            let mut f = calculus::Expression::new(body, var, l, "integrate")?;
+            Ok(Scalar(calculus::integral(&mut f, a, b)?.value))
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "integrate",
+            )))
+        }
+    });
+
+    // solve(expression, x, guess) uses Newton's method, solve(expression, x, a, b) searches between a and b
+    f.insert(String::from("solve"), |args, l| match args {
+        [body, var, guess] => {
+            let guess = scalar_param(guess, l, "solve")?;
+            let mut f = calculus::Expression::new(body, var, l, "solve")?;
+            Ok(Scalar(calculus::newton(&mut f, guess)?))
+        }
+        [body, var, a, b] => {
+            let (a, b) = (scalar_param(a, l, "solve")?, scalar_param(b, l, "solve")?);
+            let mut f = calculus::Expression::new(body, var, l, "solve")?;
+            Ok(Scalar(calculus::root_between(&mut f, a, b)?))
+        }
+        _ => Err(CalculatorError::MissingFunctionParameters(String::from(
+            "solve",
+        ))),
+    });
+    // roots(expression, x, a, b) finds every root between a and b
+    f.insert(String::from("roots"), |args, l| {
+        if let [body, var, a, b] = args {
+            let (a, b) = (scalar_param(a, l, "roots")?, scalar_param(b, l, "roots")?);
+            let mut f = calculus::Expression::new(body, var, l, "roots")?;
+            Ok(Vector(calculus::roots(&mut f, a, b)?))
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "roots",
+            )))
+        }
+    });
+
+    // ode(expression, t0, y0, t_end) is y at t_end, where the expression gives dy/dt from t and y
+    f.insert(String::from("ode"), |args, l| {
+        if let [body, t0, y0, t_end] = args {
+            let (t0, t_end) = (scalar_param(t0, l, "ode")?, scalar_param(t_end, l, "ode")?);
+            let (mut system, y0) = ode::System::new(body, &evaluate(y0, l)?, l, "ode")?;
+            let mut path = ode::solve(&mut system, t0, y0, t_end, 1)?;
+            Ok(system.to_num(path.pop().unwrap_or_default()))
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "ode",
+            )))
+        }
+    });
+    // ode_path(expression, t0, y0, t_end, n) is y at n + 1 evenly spaced times from t0 to t_end
+    // Systems give the components at each time one after another
+    f.insert(String::from("ode_path"), |args, l| {
+        if let [body, t0, y0, t_end, n] = args {
+            let (t0, t_end) = (
+                scalar_param(t0, l, "ode_path")?,
+                scalar_param(t_end, l, "ode_path")?,
+            );
+            let n = to_integer(scalar_param(n, l, "ode_path")?, "ode_path")?;
+            let (mut system, y0) = ode::System::new(body, &evaluate(y0, l)?, l, "ode_path")?;
+            if n < 1 {
+                return Err(CalculatorError::DomainError(String::from("ode_path")));
+            }
+            let len = (n as usize).saturating_add(1).saturating_mul(y0.len());
+            if len > sequences::MAX_LEN {
+                return Err(CalculatorError::VectorTooLong(sequences::MAX_LEN));
+            }
+            let path = ode::solve(&mut system, t0, y0, t_end, n as usize)?;
+            Ok(Vector(path.concat()))
+        } else {
+            Err(CalculatorError::MissingFunctionParameters(String::from(
+                "ode_path",
+            )))
+        }
+    });
+
+    // minimize(expression, x, a, b) and maximize(...) give [x, value] at the extreme between a and b
+    f.insert(String::from("minimize"), |args, l| {
+        extremum_params(args, l, "minimize", false)
+    });
+    f.insert(String::from("maximize"), |args, l| {
+        extremum_params(args, l, "maximize", true)
+    });
+
+    f
+}
+
+// Evaluates a parameter of a lazy function which has to be a number
+fn scalar_param(arg: &str, l: &Log, name: &str) -> Result<f64, CalculatorError> {
+    match evaluate(arg, l)? {
+        Scalar(n) => Ok(n),
+        _ => Err(CalculatorError::MissingFunctionParameters(name.to_owned())),
+    }
+}
+
+// Shared by deriv and deriv_err, which pick out the value or error of the estimate
+// The derivative is taken at every point if given a vector
+fn derivative_params(
+    args: &[String],
+    l: &Log,
+    name: &str,
+    pick: fn(calculus::Estimate) -> Result<f64, CalculatorError>,
+) -> Result<NumType, CalculatorError> {
+    let (body, var, at, order) = match args {
+        [body, var, at] => (body, var, at, None),
+        [body, var, at, order] => (body, var, at, Some(order)),
+        _ => return Err(CalculatorError::MissingFunctionParameters(name.to_owned())),
+    };
+    let order = match order {
+        Some(order) => to_integer(scalar_param(order, l, name)?, name)?,
+        None => 1,
+    };
+    if !(0..=calculus::MAX_ORDER as i64).contains(&order) {
+        return Err(CalculatorError::DomainError(name.to_owned()));
+    }
+    let at = evaluate(at, l)?;
+    let mut f = calculus::Expression::new(body, var, l, name)?;
+    at.try_map(|x| pick(calculus::derivative(&mut f, x, order as u32)?))
+}
+
+// Shared by minimize and maximize
+fn extremum_params(
+    args: &[String],
+    l: &Log,
+    name: &str,
+    maximize: bool,
+) -> Result<NumType, CalculatorError> {
+    if let [body, var, a, b] = args {
+        let (a, b) = (scalar_param(a, l, name)?, scalar_param(b, l, name)?);
+        let mut f = calculus::Expression::new(body, var, l, name)?;
+        let (x, y) = calculus::extremum(&mut f, a, b, maximize)?;
+        Ok(Vector(vec![x, y]))
+    } else {
+        Err(CalculatorError::MissingFunctionParameters(name.to_owned()))
+    }
+}
+
+// Built in names are matched case-insensitively, so every lowercase spelling maps back to the real name
+// Any extra spellings for built in symbols go here as well
+pub fn get_aliases_hashmap<'a>(names: impl Iterator<Item = &'a String>) -> HashMap<String, String> {
+    let mut a: HashMap<String, String> = HashMap::new();
+    for name in names {
+        a.insert(name.to_ascii_lowercase(), name.to_owned());
+    }
+
+    a
+}
+
+pub fn get_default_commands_hashmap() -> HashMap<String, CommandFn> {
+    let mut c: HashMap<String, CommandFn> = HashMap::new();
+    c.insert(String::from("clear"), |l, _| {
         l.clear();
         String::from("All data cleared")
     });
-    c.insert(String::from("clearvars"), |l| {
+    c.insert(String::from("clearvars"), |l, _| {
         l.clear_vars();
         String::from("Variable data cleared")
     });
-    c.insert(String::from("clearhistory"), |l| {
+    c.insert(String::from("clearhistory"), |l, _| {
         l.clear_history();
         String::from("Calculator history cleared")
     });
+    c.insert(String::from("rad"), |l, _| {
+        l.angle_mode = AngleMode::Radians;
+        String::from("Angles are now in radians")
+    });
+    c.insert(String::from("deg"), |l, _| {
+        l.angle_mode = AngleMode::Degrees;
+        String::from("Angles are now in degrees")
+    });
+    c.insert(String::from("grad"), |l, _| {
+        l.angle_mode = AngleMode::Gradians;
+        String::from("Angles are now in gradians")
+    });
+
+    // Programmer mode, takes an integer type such as u8 or i32 and optionally "wrap" or "checked"
+    c.insert(String::from("int"), |l, arg| {
+        let mut args = arg.split_whitespace();
+        let type_name = args.next().unwrap_or_default().to_ascii_lowercase();
+        if type_name == "off" {
+            l.int_mode = None;
+            return String::from("Programmer mode off");
+        }
+        let overflow = match args.next() {
+            None | Some("wrap") | Some("wrapping") => Overflow::Wrapping,
+            Some("checked") => Overflow::Checked,
+            Some(_) => {
+                return CalculatorError::InvalidCommandArgument(String::from("int")).to_string()
+            }
+        };
+        match IntMode::from_type_name(&type_name, overflow) {
+            Some(mode) => {
+                l.int_mode = Some(mode);
+                format!("Programmer mode on, using {} integers", mode)
+            }
+            None => CalculatorError::InvalidCommandArgument(String::from("int")).to_string(),
+        }
+    });
+
+    // Number formatting commands, most take the number of digits to show
+    c.insert(String::from("smart"), |l, _| {
+        l.format.notation = Notation::Smart;
+        format!("Numbers are now shown in {}", l.format.notation)
+    });
+    c.insert(String::from("fix"), |l, arg| {
+        set_notation(l, arg, "fix", Notation::Fixed)
+    });
+    c.insert(String::from("sig"), |l, arg| {
+        set_notation(l, arg, "sig", Notation::Significant)
+    });
+    c.insert(String::from("sci"), |l, arg| {
+        set_notation(l, arg, "sci", Notation::Scientific)
+    });
+    c.insert(String::from("eng"), |l, arg| {
+        set_notation(l, arg, "eng", Notation::Engineering)
+    });
+    c.insert(String::from("base"), |l, arg| {
+        let base = match arg.trim() {
+            "hex" => 16,
+            "dec" => 10,
+            "oct" => 8,
+            "bin" => 2,
+            n => match n.parse::<u32>() {
+                Ok(n) if (2..=36).contains(&n) => n,
+                _ => {
+                    return CalculatorError::InvalidCommandArgument(String::from("base"))
+                        .to_string()
+                }
+            },
+        };
+        l.format.base = base;
+        format!("Whole numbers are now shown in base {}", base)
+    });
+    c.insert(String::from("lenient"), |l, _| {
+        l.lenient_vectors = !l.lenient_vectors;
+        if l.lenient_vectors {
+            String::from("Lenient vectors on, mismatched lengths are padded with 1s")
+        } else {
+            String::from("Lenient vectors off, vector lengths must match")
+        }
+    });
+    // /seed n makes random numbers repeat from n, /seed on its own goes back to the clock
+    c.insert(String::from("seed"), |l, arg| match arg.trim() {
+        "" => {
+            l.random.reseed_from_clock();
+            String::from("Random numbers are seeded from the clock")
+        }
+        n => match n.parse::<u64>() {
+            Ok(seed) => {
+                l.random.seed(seed);
+                format!("Random numbers are seeded with {}", seed)
+            }
+            Err(_) => CalculatorError::InvalidCommandArgument(String::from("seed")).to_string(),
+        },
+    });
+    c.insert(String::from("group"), |l, _| {
+        l.format.group_thousands = !l.format.group_thousands;
+        if l.format.group_thousands {
+            String::from("Thousands separators on")
+        } else {
+            String::from("Thousands separators off")
+        }
+    });
 
     c
 }
+
+// Gathers the numbers from every parameter into one list, so either a vector or several numbers can be given
+fn data_params(v: &[NumType], name: &str) -> Result<Vec<f64>, CalculatorError> {
+    let mut data = Vec::new();
+    for n in v {
+        match n {
+            Scalar(s) => data.push(*s),
+            Vector(v) => data.extend(v),
+            other => return Err(other.not_real()),
+        }
+    }
+    if data.is_empty() {
+        Err(CalculatorError::EmptyInput(name.to_owned()))
+    } else {
+        Ok(data)
+    }
+}
+
+// A random number, or a vector of count of them if a count is given
+fn random_draws(
+    count: Option<&NumType>,
+    name: &str,
+    mut draw: impl FnMut() -> f64,
+) -> Result<NumType, CalculatorError> {
+    match count {
+        None => Ok(Scalar(draw())),
+        Some(Scalar(n)) => {
+            let n = to_integer(*n, name)?;
+            if n < 0 {
+                Err(CalculatorError::DomainError(name.to_owned()))
+            } else if n as usize > sequences::MAX_LEN {
+                Err(CalculatorError::VectorTooLong(sequences::MAX_LEN))
+            } else {
+                Ok(Vector((0..n).map(|_| draw()).collect()))
+            }
+        }
+        Some(_) => Err(CalculatorError::MissingFunctionParameters(name.to_owned())),
+    }
+}
+
+// Shared by the distribution functions, which take x followed by the distribution's parameters
+// Parameters left out use their defaults, if they have one
+fn distribution(
+    v: &[NumType],
+    defaults: &[Option<f64>],
+    name: &str,
+    f: impl Fn(f64, &[f64]) -> Result<f64, CalculatorError>,
+) -> Result<NumType, CalculatorError> {
+    let Some(x) = v.first() else {
+        return Err(CalculatorError::MissingFunctionParameters(name.to_owned()));
+    };
+    let params = defaults
+        .iter()
+        .enumerate()
+        .map(|(i, default)| match v.get(i + 1) {
+            Some(Scalar(p)) => Ok(*p),
+            None => {
+                default.ok_or_else(|| CalculatorError::MissingFunctionParameters(name.to_owned()))
+            }
+            _ => Err(CalculatorError::MissingFunctionParameters(name.to_owned())),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    x.clone().try_map(|x| f(x, &params))
+}
+
+// The numbers in a scalar or vector parameter, for functions which go through them one at a time
+fn components(n: &NumType, name: &str) -> Result<Vec<f64>, CalculatorError> {
+    match n {
+        Scalar(s) => Ok(vec![*s]),
+        Vector(v) => Ok(v.clone()),
+        _ => Err(CalculatorError::MissingFunctionParameters(name.to_owned())),
+    }
+}
+
+// The values of a real or complex vector as complex numbers, which must not be empty
+fn complex_params(v: &[NumType], name: &str) -> Result<Vec<num_types::Complex>, CalculatorError> {
+    let x = match v.first() {
+        Some(Complex(x)) => x.clone(),
+        Some(n) => components(n, name)?
+            .iter()
+            .map(|x| num_types::Complex::new(*x, 0.0))
+            .collect(),
+        None => return Err(CalculatorError::MissingFunctionParameters(name.to_owned())),
+    };
+    if x.is_empty() {
+        Err(CalculatorError::EmptyInput(name.to_owned()))
+    } else {
+        Ok(x)
+    }
+}
+
+// Evaluates the parameters of a lazy function and passes them to the ordinary function with the same name
+fn call_eagerly(args: &[String], l: &Log, name: &str) -> Result<NumType, CalculatorError> {
+    let v = args
+        .iter()
+        .map(|a| evaluate(a, l))
+        .collect::<Result<_, _>>()?;
+    l.default_functions[name](v, l)
+}
+
+// Reads the first count parameters as whole numbers
+fn integer_params(v: &[NumType], count: usize, name: &str) -> Result<Vec<i64>, CalculatorError> {
+    match v.get(0..count) {
+        Some(params) => params
+            .iter()
+            .map(|n| match n {
+                Scalar(s) => to_exact_integer(*s, name),
+                _ => Err(CalculatorError::MissingFunctionParameters(name.to_owned())),
+            })
+            .collect(),
+        None => Err(CalculatorError::MissingFunctionParameters(name.to_owned())),
+    }
+}
+
+// Whole number results past 2^53 would be rounded when stored, so they are refused like parameters are
+fn exact_result(n: u64, name: &str) -> Result<NumType, CalculatorError> {
+    if n <= num_types::MAX_EXACT_INTEGER {
+        Ok(Scalar(n as f64))
+    } else {
+        Err(CalculatorError::BeyondExactIntegers(name.to_owned()))
+    }
+}
+
+// Shared by the formatting commands which need a digit count
+fn set_notation(l: &mut Log, arg: &str, name: &str, notation: fn(usize) -> Notation) -> String {
+    match arg.trim().parse::<usize>().map(notation) {
+        Ok(notation) if notation.digits_in_range() => {
+            l.format.notation = notation;
+            format!("Numbers are now shown in {}", l.format.notation)
+        }
+        _ => CalculatorError::InvalidCommandArgument(name.to_owned()).to_string(),
+    }
+}
@@ -0,0 +1,607 @@
+use crate::calculator::num_types::NumType;
+use crate::calculator::{evaluate, CalculatorError};
+use crate::log::Log;
+
+// An expression in one variable, such as x^2 + sin(x), evaluated in a scoped copy of the log
+// so the variable doesn't overwrite anything outside
+pub struct Expression<'a> {
+    body: &'a str,
+    var: &'a str,
+    name: &'a str,
+    scope: Log,
+}
+
+impl<'a> Expression<'a> {
+    pub fn new(
+        body: &'a str,
+        var: &'a str,
+        log: &Log,
+        name: &'a str,
+    ) -> Result<Self, CalculatorError> {
+        let var = var.trim();
+        log.validate_name(var)?;
+        Ok(Expression {
+            body,
+            var,
+            name,
+            scope: log.scope()?,
+        })
+    }
+
+    pub fn eval(&mut self, x: f64) -> Result<f64, CalculatorError> {
+        self.scope.add_var(self.var.to_owned(), &NumType::Scalar(x));
+        match evaluate(self.body, &self.scope)? {
+            NumType::Scalar(y) => Ok(y),
+            _ => Err(CalculatorError::MissingFunctionParameters(
+                self.name.to_owned(),
+            )),
+        }
+    }
+}
+
+// A result along with an estimate of how far off it might be
+pub struct Estimate {
+    pub value: f64,
+    pub error: f64,
+}
+
+impl Estimate {
+    // The value, unless the error is too large to trust it
+    // Past this, the result most likely doesn't exist (the derivative of 1/x at 0)
+    pub fn accurate(self, name: &str) -> Result<f64, CalculatorError> {
+        const TOLERANCE: f64 = 1e-6;
+        if self.error <= TOLERANCE * self.value.abs().max(1.0) {
+            Ok(self.value)
+        } else {
+            Err(CalculatorError::ConvergenceFailure(name.to_owned()))
+        }
+    }
+}
+
+// Highest derivative deriv will take, past this rounding errors swamp the result
+pub const MAX_ORDER: u32 = 4;
+
+// Central difference of the given order with step h, using the binomial coefficients
+// f^(n)(x) ~ sum (-1)^k nCk f(x + (n/2 - k)h) / h^n
+fn central_difference(
+    f: &mut Expression,
+    x: f64,
+    h: f64,
+    order: u32,
+) -> Result<f64, CalculatorError> {
+    let mut total = 0.0;
+    let mut coefficient = 1.0;
+    for k in 0..=order {
+        let offset = (order as f64 / 2.0 - k as f64) * h;
+        total += coefficient * f.eval(x + offset)?;
+        coefficient *= -((order - k) as f64) / (k + 1) as f64;
+    }
+    Ok(total / h.powi(order as i32))
+}
+
+// Ridders' method, central differences with shrinking steps are extrapolated towards a step of 0
+// The differences between extrapolations give the error estimate
+pub fn derivative(f: &mut Expression, x: f64, order: u32) -> Result<Estimate, CalculatorError> {
+    if order == 0 {
+        return Ok(Estimate {
+            value: f.eval(x)?,
+            error: 0.0,
+        });
+    }
+
+    const SHRINK: f64 = 1.4;
+    const STEPS: usize = 20;
+    // Stop once the extrapolations start getting worse by this factor
+    const SAFE: f64 = 2.0;
+
+    // Smaller starting steps close to 0 keep away from edges such as ln(x) at 0
+    let mut h = 0.1 * x.abs().clamp(0.01, 1.0);
+    let mut table: Vec<Vec<f64>> = Vec::with_capacity(STEPS);
+    let mut best = Estimate {
+        value: f64::NAN,
+        error: f64::INFINITY,
+    };
+    for _ in 0..STEPS {
+        let d = central_difference(f, x, h, order)?;
+        h /= SHRINK;
+        // A step which reaches past the edge of the domain, such as ln(x) near 0, is skipped
+        if !d.is_finite() {
+            table.clear();
+            continue;
+        }
+
+        let mut row = vec![d];
+        let mut factor = SHRINK * SHRINK;
+        for j in 1..=table.len() {
+            let previous = &table[table.len() - 1];
+            let extrapolated = (row[j - 1] * factor - previous[j - 1]) / (factor - 1.0);
+            factor *= SHRINK * SHRINK;
+            let error = (extrapolated - row[j - 1])
+                .abs()
+                .max((extrapolated - previous[j - 1]).abs());
+            if error <= best.error {
+                best = Estimate {
+                    value: extrapolated,
+                    error,
+                };
+            }
+            row.push(extrapolated);
+        }
+        if let Some(previous) = table.last() {
+            if (row[row.len() - 1] - previous[previous.len() - 1]).abs() >= SAFE * best.error {
+                break;
+            }
+        }
+        table.push(row);
+    }
+
+    if best.value.is_finite() && !(order == 1 && is_kink(f, x, best.value)?) {
+        Ok(best)
+    } else {
+        Err(CalculatorError::ConvergenceFailure(f.name.to_owned()))
+    }
+}
+
+// Whether the slopes either side of x disagree, as with abs(x) at 0 where central differences
+// average them out to a derivative which doesn't exist
+// For a smooth function the gap between them shrinks with the step, at a kink it stays put
+fn is_kink(f: &mut Expression, x: f64, slope: f64) -> Result<bool, CalculatorError> {
+    let h = 1e-4 * x.abs().clamp(0.01, 1.0);
+    let mut gap = |h: f64| -> Result<f64, CalculatorError> {
+        let (left, middle, right) = (f.eval(x - h)?, f.eval(x)?, f.eval(x + h)?);
+        Ok((right - 2.0 * middle + left).abs() / h)
+    };
+    let (wide, narrow) = (gap(h)?, gap(h / 10.0)?);
+    // Rounding errors in f grow as the step shrinks, and are no sign of a kink
+    let noise = 100.0 * f64::EPSILON * f.eval(x)?.abs() / (h / 10.0);
+    let threshold = (1e-6 * slope.abs().max(1.0)).max(noise);
+    Ok(narrow.is_finite() && narrow > 0.5 * wide && narrow > threshold)
+}
+
+// Nodes and weights of the 15 point Kronrod rule, with the embedded 7 point Gauss rule
+// using every other node. Only the positive half is listed, the last node is the center
+const KRONROD_NODES: [f64; 8] = [
+    0.9914553711208126,
+    0.9491079123427585,
+    0.8648644233597691,
+    0.7415311855993945,
+    0.5860872354676911,
+    0.4058451513773972,
+    0.20778495500789848,
+    0.0,
+];
+const KRONROD_WEIGHTS: [f64; 8] = [
+    0.022935322010529224,
+    0.06309209262997856,
+    0.10479001032225019,
+    0.14065325971552592,
+    0.1690047266392679,
+    0.19035057806478542,
+    0.20443294007529889,
+    0.20948214108472782,
+];
+const GAUSS_WEIGHTS: [f64; 4] = [
+    0.1294849661688697,
+    0.27970539148927664,
+    0.3818300505051189,
+    0.4179591836734694,
+];
+
+// One Gauss-Kronrod step over [a, b], the difference between the two rules is the error estimate
+fn gauss_kronrod(
+    f: &mut impl FnMut(f64) -> Result<f64, CalculatorError>,
+    a: f64,
+    b: f64,
+) -> Result<Estimate, CalculatorError> {
+    let center = (a + b) / 2.0;
+    let half = (b - a) / 2.0;
+    let fc = f(center)?;
+    let mut kronrod = fc * KRONROD_WEIGHTS[7];
+    let mut gauss = fc * GAUSS_WEIGHTS[3];
+    for j in 0..7 {
+        let offset = half * KRONROD_NODES[j];
+        let pair = f(center - offset)? + f(center + offset)?;
+        kronrod += KRONROD_WEIGHTS[j] * pair;
+        if j % 2 == 1 {
+            gauss += GAUSS_WEIGHTS[j / 2] * pair;
+        }
+    }
+    Ok(Estimate {
+        value: kronrod * half,
+        error: ((kronrod - gauss) * half).abs(),
+    })
+}
+
+// Splits the interval with the largest error in two until the total error is small enough
+fn adaptive_integral(
+    f: &mut impl FnMut(f64) -> Result<f64, CalculatorError>,
+    a: f64,
+    b: f64,
+    name: &str,
+) -> Result<Estimate, CalculatorError> {
+    const TOLERANCE: f64 = 1e-10;
+    const MAX_INTERVALS: usize = 500;
+
+    let mut intervals = vec![(a, b, gauss_kronrod(f, a, b)?)];
+    loop {
+        let value: f64 = intervals.iter().map(|i| i.2.value).sum();
+        let error: f64 = intervals.iter().map(|i| i.2.error).sum();
+        if !value.is_finite() || !error.is_finite() {
+            return Err(CalculatorError::ConvergenceFailure(name.to_owned()));
+        }
+        if error <= TOLERANCE * value.abs().max(1.0) {
+            return Ok(Estimate { value, error });
+        }
+        if intervals.len() >= MAX_INTERVALS {
+            return Err(CalculatorError::ConvergenceFailure(name.to_owned()));
+        }
+
+        let worst = (0..intervals.len())
+            .max_by(|i, j| intervals[*i].2.error.total_cmp(&intervals[*j].2.error))
+            .expect("There is always at least one interval");
+        let (a, b, _) = intervals.swap_remove(worst);
+        let middle = (a + b) / 2.0;
+        // The interval can't be split any further
+        if middle <= a || middle >= b {
+            return Err(CalculatorError::ConvergenceFailure(name.to_owned()));
+        }
+        intervals.push((a, middle, gauss_kronrod(f, a, middle)?));
+        intervals.push((middle, b, gauss_kronrod(f, middle, b)?));
+    }
+}
+
+// Definite integral of the expression from a to b, either of which may be infinite
+// Infinite ranges are mapped onto finite ones by substituting x for a function of t
+pub fn integral(f: &mut Expression, a: f64, b: f64) -> Result<Estimate, CalculatorError> {
+    let name = f.name.to_owned();
+    if a.is_nan() || b.is_nan() {
+        return Err(CalculatorError::DomainError(name));
+    }
+    if a == b {
+        return Ok(Estimate {
+            value: 0.0,
+            error: 0.0,
+        });
+    }
+    if a > b {
+        let flipped = integral(f, b, a)?;
+        return Ok(Estimate {
+            value: -flipped.value,
+            ..flipped
+        });
+    }
+
+    match (a.is_finite(), b.is_finite()) {
+        (true, true) => adaptive_integral(&mut |x| f.eval(x), a, b, &name),
+        // x = a + t / (1 - t) for t from 0 to 1
+        (true, false) => adaptive_integral(
+            &mut |t| Ok(f.eval(a + t / (1.0 - t))? / (1.0 - t).powi(2)),
+            0.0,
+            1.0,
+            &name,
+        ),
+        // x = b - (1 - t) / t for t from 0 to 1
+        (false, true) => adaptive_integral(
+            &mut |t| Ok(f.eval(b - (1.0 - t) / t)? / t.powi(2)),
+            0.0,
+            1.0,
+            &name,
+        ),
+        // x = t / (1 - t^2) for t from -1 to 1
+        (false, false) => adaptive_integral(
+            &mut |t| {
+                let s = 1.0 - t * t;
+                Ok(f.eval(t / s)? * (1.0 + t * t) / (s * s))
+            },
+            -1.0,
+            1.0,
+            &name,
+        ),
+    }
+}
+
+// Checks whether a value is close enough to 0 to count as a root
+fn is_root(y: f64) -> bool {
+    y.abs() <= 1e-9
+}
+
+// Newton's method from a starting guess, using deriv for the slope
+pub fn newton(f: &mut Expression, guess: f64) -> Result<f64, CalculatorError> {
+    const MAX_ITERATIONS: usize = 100;
+    let mut x = guess;
+    for _ in 0..MAX_ITERATIONS {
+        let y = f.eval(x)?;
+        let slope = derivative(f, x, 1)?.value;
+        if slope == 0.0 || !slope.is_finite() {
+            break;
+        }
+        let step = y / slope;
+        x -= step;
+        if !x.is_finite() {
+            break;
+        }
+        if step.abs() <= 1e-12 * x.abs().max(1.0) {
+            return if is_root(f.eval(x)?) {
+                Ok(x)
+            } else {
+                break;
+            };
+        }
+    }
+    Err(CalculatorError::ConvergenceFailure(f.name.to_owned()))
+}
+
+// Brent's method, which combines bisection with interpolation and needs the expression to change sign
+// between a and b. The result is only a root if the expression is continuous there
+pub fn brent(f: &mut Expression, a: f64, b: f64) -> Result<f64, CalculatorError> {
+    const MAX_ITERATIONS: usize = 200;
+    let (mut a, mut b) = (a, b);
+    let (mut fa, mut fb) = (f.eval(a)?, f.eval(b)?);
+    if fa == 0.0 {
+        return Ok(a);
+    }
+    if fb == 0.0 {
+        return Ok(b);
+    }
+    if fa.signum() == fb.signum() || fa.is_nan() || fb.is_nan() {
+        return Err(CalculatorError::NoRoots(f.var.to_owned()));
+    }
+
+    let (mut c, mut fc) = (a, fa);
+    let mut d = b - a;
+    let mut e = d;
+    for _ in 0..MAX_ITERATIONS {
+        if fb.signum() == fc.signum() {
+            c = a;
+            fc = fa;
+            d = b - a;
+            e = d;
+        }
+        // Keep b as the best guess so far
+        if fc.abs() < fb.abs() {
+            a = b;
+            b = c;
+            c = a;
+            fa = fb;
+            fb = fc;
+            fc = fa;
+        }
+        let tolerance = 2.0 * f64::EPSILON * b.abs() + 1e-15;
+        let middle = (c - b) / 2.0;
+        if middle.abs() <= tolerance || fb == 0.0 {
+            return Ok(b);
+        }
+        if e.abs() >= tolerance && fa.abs() > fb.abs() {
+            // Try interpolating, through a secant or an inverse quadratic
+            let s = fb / fa;
+            let (mut p, mut q) = if a == c {
+                (2.0 * middle * s, 1.0 - s)
+            } else {
+                let q = fa / fc;
+                let r = fb / fc;
+                (
+                    s * (2.0 * middle * q * (q - r) - (b - a) * (r - 1.0)),
+                    (q - 1.0) * (r - 1.0) * (s - 1.0),
+                )
+            };
+            if p > 0.0 {
+                q = -q;
+            }
+            p = p.abs();
+            // Only accept the interpolation if it stays inside the bracket and converges fast enough
+            if 2.0 * p < (3.0 * middle * q - (tolerance * q).abs()).min((e * q).abs()) {
+                e = d;
+                d = p / q;
+            } else {
+                d = middle;
+                e = d;
+            }
+        } else {
+            d = middle;
+            e = d;
+        }
+        a = b;
+        fa = fb;
+        b += if d.abs() > tolerance {
+            d
+        } else {
+            tolerance.copysign(middle)
+        };
+        fb = f.eval(b)?;
+    }
+    Err(CalculatorError::ConvergenceFailure(f.name.to_owned()))
+}
+
+// A root between a and b by Brent's method, checking that it isn't a discontinuity such as 1/x at 0
+pub fn root_between(f: &mut Expression, a: f64, b: f64) -> Result<f64, CalculatorError> {
+    let x = brent(f, a, b)?;
+    if is_root(f.eval(x)?) {
+        Ok(x)
+    } else {
+        Err(CalculatorError::NoRoots(f.var.to_owned()))
+    }
+}
+
+// Every root found between a and b, by sampling the expression and looking for where it crosses or
+// touches 0. Roots closer together than the samples may be missed
+pub fn roots(f: &mut Expression, a: f64, b: f64) -> Result<Vec<f64>, CalculatorError> {
+    const SAMPLES: usize = 1000;
+    if !a.is_finite() || !b.is_finite() || a >= b {
+        return Err(CalculatorError::DomainError(f.name.to_owned()));
+    }
+
+    let xs: Vec<f64> = (0..=SAMPLES)
+        .map(|i| a + (b - a) * i as f64 / SAMPLES as f64)
+        .collect();
+    let ys = xs
+        .iter()
+        .map(|x| f.eval(*x))
+        .collect::<Result<Vec<_>, _>>()?;
+    // Every sample being a root means the expression is 0 everywhere, as x - x is
+    if ys.iter().all(|y| is_root(*y)) {
+        return Err(CalculatorError::EveryValueRoot(f.var.to_owned()));
+    }
+
+    let mut found = Vec::new();
+    for i in 0..SAMPLES {
+        if ys[i] == 0.0 {
+            found.push(xs[i]);
+        } else if ys[i].signum() != ys[i + 1].signum() && ys[i + 1] != 0.0 {
+            // A change of sign is either a root or a discontinuity such as 1/x at 0
+            if let Ok(x) = root_between(f, xs[i], xs[i + 1]) {
+                found.push(x);
+            }
+        } else if i > 0 && ys[i].abs() < ys[i - 1].abs() && ys[i].abs() <= ys[i + 1].abs() {
+            // The expression gets close to 0 without crossing, it might just touch it (x^2 at 0)
+            if let Ok(x) = newton(f, xs[i]) {
+                if xs[i - 1] <= x && x <= xs[i + 1] {
+                    found.push(x);
+                }
+            }
+        }
+    }
+    if ys[SAMPLES] == 0.0 {
+        found.push(b);
+    }
+
+    found.sort_by(f64::total_cmp);
+    found.dedup_by(|x, y| (*x - *y).abs() <= 1e-9 * x.abs().max(1.0));
+    Ok(found)
+}
+
+// Smallest value of the expression between a and b (or largest, when maximizing) as (x, value)
+// The expression is sampled first so Brent's search starts next to the best sample rather than
+// stopping at whichever local minimum it finds first. The ends are checked separately, since the
+// search never lands exactly on them
+pub fn extremum(
+    f: &mut Expression,
+    a: f64,
+    b: f64,
+    maximize: bool,
+) -> Result<(f64, f64), CalculatorError> {
+    const SAMPLES: usize = 100;
+    if !a.is_finite() || !b.is_finite() || a >= b {
+        return Err(CalculatorError::DomainError(f.name.to_owned()));
+    }
+    // Everything is minimized, so maximizing flips the sign. Undefined points are never the best
+    let sign = if maximize { -1.0 } else { 1.0 };
+    let name = f.name;
+    let mut g = |x: f64| -> Result<f64, CalculatorError> {
+        let y = sign * f.eval(x)?;
+        Ok(if y.is_nan() { f64::INFINITY } else { y })
+    };
+
+    let xs: Vec<f64> = (0..=SAMPLES)
+        .map(|i| a + (b - a) * i as f64 / SAMPLES as f64)
+        .collect();
+    let mut best = (a, g(a)?);
+    for x in &xs[1..] {
+        let y = g(*x)?;
+        if y < best.1 {
+            best = (*x, y);
+        }
+    }
+    let i = xs.iter().position(|x| *x == best.0).unwrap_or(0);
+    let (low, high) = (xs[i.saturating_sub(1)], xs[(i + 1).min(SAMPLES)]);
+    let (x, y) = brent_minimum(&mut g, low, high, best, name)?;
+    // Near a discontinuity such as 1/x at 0 the search homes in on the jump, which isn't a minimum
+    // Close to a real minimum the expression barely changes
+    let h = 1e-6 * x.abs().max(1.0);
+    for y_near in [g(x - h)?, g(x + h)?] {
+        if !y_near.is_finite() || (y_near - y).abs() > 1e-3 * y.abs().max(1.0) {
+            return Err(CalculatorError::ConvergenceFailure(name.to_owned()));
+        }
+    }
+    let best = [(x, y), (a, g(a)?), (b, g(b)?)]
+        .into_iter()
+        .fold(best, |best, p| if p.1 < best.1 { p } else { best });
+
+    // An infinite best value means the expression has no smallest value there (1/x around 0)
+    if !best.1.is_finite() {
+        return Err(CalculatorError::ConvergenceFailure(name.to_owned()));
+    }
+    Ok((best.0, sign * best.1))
+}
+
+// Brent's minimization, golden section steps sped up by fitting parabolas through the best points
+// Starts from a point between low and high, and gives the best point found
+fn brent_minimum(
+    g: &mut impl FnMut(f64) -> Result<f64, CalculatorError>,
+    low: f64,
+    high: f64,
+    start: (f64, f64),
+    name: &str,
+) -> Result<(f64, f64), CalculatorError> {
+    const MAX_ITERATIONS: usize = 200;
+    // The golden section ratio, (3 - sqrt(5)) / 2
+    const GOLDEN: f64 = 0.381_966_011_250_105_1;
+    // Minimums can only be found to about the square root of the precision, the curve is too flat
+    let tolerance = f64::EPSILON.sqrt();
+
+    let (mut a, mut b) = (low, high);
+    let (mut x, mut fx) = start;
+    let (mut w, mut fw, mut v, mut fv) = (x, fx, x, fx);
+    let (mut d, mut e) = (0.0_f64, 0.0_f64);
+    for _ in 0..MAX_ITERATIONS {
+        let middle = (a + b) / 2.0;
+        let tol1 = tolerance * x.abs() + 1e-12;
+        let tol2 = 2.0 * tol1;
+        if (x - middle).abs() <= tol2 - (b - a) / 2.0 {
+            return Ok((x, fx));
+        }
+
+        let mut golden = true;
+        if e.abs() > tol1 {
+            // Try the lowest point of the parabola through x, w and v
+            let r = (x - w) * (fx - fv);
+            let mut q = (x - v) * (fx - fw);
+            let mut p = (x - v) * q - (x - w) * r;
+            q = 2.0 * (q - r);
+            if q > 0.0 {
+                p = -p;
+            }
+            q = q.abs();
+            // Only accept it if it lands inside the bracket and moves less than half the step before last
+            if p.abs() < (q * e / 2.0).abs() && p > q * (a - x) && p < q * (b - x) {
+                e = d;
+                d = p / q;
+                let u = x + d;
+                if u - a < tol2 || b - u < tol2 {
+                    d = tol1.copysign(middle - x);
+                }
+                golden = false;
+            }
+        }
+        if golden {
+            e = if x >= middle { a - x } else { b - x };
+            d = GOLDEN * e;
+        }
+
+        let u = if d.abs() >= tol1 {
+            x + d
+        } else {
+            x + tol1.copysign(d)
+        };
+        let fu = g(u)?;
+        if fu <= fx {
+            if u >= x {
+                a = x;
+            } else {
+                b = x;
+            }
+            (v, fv, w, fw, x, fx) = (w, fw, x, fx, u, fu);
+        } else {
+            if u < x {
+                a = u;
+            } else {
+                b = u;
+            }
+            if fu <= fw || w == x {
+                (v, fv, w, fw) = (w, fw, u, fu);
+            } else if fu <= fv || v == x || v == w {
+                (v, fv) = (u, fu);
+            }
+        }
+    }
+    Err(CalculatorError::ConvergenceFailure(name.to_owned()))
+}
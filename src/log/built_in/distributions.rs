@@ -0,0 +1,343 @@
+use super::number_theory::ncr;
+use super::special::{beta_regularized, erfc, gamma_q, lgamma};
+use crate::calculator::CalculatorError;
+use std::f64::consts::{PI, SQRT_2};
+
+// pdf gives the density (or probability of exactly k for the discrete ones), cdf the probability of
+// at most x, and inv the x where the cdf reaches a probability q
+
+fn domain_error(name: &str) -> CalculatorError {
+    CalculatorError::DomainError(name.to_owned())
+}
+
+// Probabilities given to the inverse functions have to be between 0 and 1
+fn check_probability(q: f64, name: &str) -> Result<(), CalculatorError> {
+    if (0.0..=1.0).contains(&q) {
+        Ok(())
+    } else {
+        Err(domain_error(name))
+    }
+}
+
+pub fn normal_pdf(x: f64, mean: f64, sd: f64) -> Result<f64, CalculatorError> {
+    if sd <= 0.0 {
+        return Err(domain_error("normpdf"));
+    }
+    let z = (x - mean) / sd;
+    Ok((-z * z / 2.0).exp() / (sd * (2.0 * PI).sqrt()))
+}
+
+pub fn normal_cdf(x: f64, mean: f64, sd: f64) -> Result<f64, CalculatorError> {
+    if sd <= 0.0 {
+        return Err(domain_error("normcdf"));
+    }
+    Ok(erfc(-(x - mean) / (sd * SQRT_2))? / 2.0)
+}
+
+pub fn normal_inv(q: f64, mean: f64, sd: f64) -> Result<f64, CalculatorError> {
+    check_probability(q, "norminv")?;
+    if sd <= 0.0 {
+        return Err(domain_error("norminv"));
+    }
+    Ok(mean + sd * standard_normal_inv(q)?)
+}
+
+// Acklam's rational approximation, followed by one step of Halley's method to reach full precision
+fn standard_normal_inv(q: f64) -> Result<f64, CalculatorError> {
+    const A: [f64; 6] = [
+        -3.969_683_028_665_376e1,
+        2.209_460_984_245_205e2,
+        -2.759_285_104_469_687e2,
+        1.383_577_518_672_69e2,
+        -3.066_479_806_614_716e1,
+        2.506_628_277_459_239,
+    ];
+    const B: [f64; 5] = [
+        -5.447_609_879_822_406e1,
+        1.615_858_368_580_409e2,
+        -1.556_989_798_598_866e2,
+        6.680_131_188_771_972e1,
+        -1.328_068_155_288_572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784_894_002_430_293e-3,
+        -3.223_964_580_411_365e-1,
+        -2.400_758_277_161_838,
+        -2.549_732_539_343_734,
+        4.374_664_141_464_968,
+        2.938_163_982_698_783,
+    ];
+    const D: [f64; 4] = [
+        7.784_695_709_041_462e-3,
+        3.224_671_290_700_398e-1,
+        2.445_134_137_142_996,
+        3.754_408_661_907_416,
+    ];
+    const LOW: f64 = 0.02425;
+
+    if q == 0.0 {
+        return Ok(f64::NEG_INFINITY);
+    }
+    if q == 1.0 {
+        return Ok(f64::INFINITY);
+    }
+    // The tails and the middle use different approximations
+    let tail = |p: f64| {
+        let r = (-2.0 * p.ln()).sqrt();
+        (((((C[0] * r + C[1]) * r + C[2]) * r + C[3]) * r + C[4]) * r + C[5])
+            / ((((D[0] * r + D[1]) * r + D[2]) * r + D[3]) * r + 1.0)
+    };
+    let x = if q < LOW {
+        tail(q)
+    } else if q > 1.0 - LOW {
+        -tail(1.0 - q)
+    } else {
+        let r = (q - 0.5) * (q - 0.5);
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * (q - 0.5)
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    };
+
+    let error = erfc(-x / SQRT_2)? / 2.0 - q;
+    let u = error * (2.0 * PI).sqrt() * (x * x / 2.0).exp();
+    // Far out in the tails the step overflows, but the approximation is already as close as it gets
+    if u.is_finite() {
+        Ok(x - u / (1.0 + x * u / 2.0))
+    } else {
+        Ok(x)
+    }
+}
+
+// The number of trials has to be a whole number and the chance of success a probability
+fn check_binomial(n: f64, p: f64, name: &str) -> Result<(), CalculatorError> {
+    if n >= 0.0 && n.fract() == 0.0 && (0.0..=1.0).contains(&p) {
+        Ok(())
+    } else {
+        Err(domain_error(name))
+    }
+}
+
+// Up to this many trials the binomial coefficient is worked out directly, so simple cases come out
+// exact, past it the sums get slow and the coefficient goes through lgamma
+const EXACT_TRIALS: f64 = 1000.0;
+
+pub fn binomial_pdf(k: f64, n: f64, p: f64) -> Result<f64, CalculatorError> {
+    check_binomial(n, p, "binompdf")?;
+    if k < 0.0 || k > n || k.fract() != 0.0 {
+        return Ok(0.0);
+    }
+    // Certain outcomes would take the log of 0
+    if p == 0.0 || p == 1.0 {
+        return Ok(if (p == 0.0 && k == 0.0) || (p == 1.0 && k == n) {
+            1.0
+        } else {
+            0.0
+        });
+    }
+    if n <= EXACT_TRIALS {
+        let direct = ncr(n as i64, k as i64)? * p.powi(k as i32) * (1.0 - p).powi((n - k) as i32);
+        // The powers underflow long before the whole product does
+        if direct.is_normal() {
+            return Ok(direct);
+        }
+    }
+    let ln_choose = lgamma(n + 1.0)? - lgamma(k + 1.0)? - lgamma(n - k + 1.0)?;
+    Ok((ln_choose + k * p.ln() + (n - k) * (1.0 - p).ln()).exp())
+}
+
+pub fn binomial_cdf(k: f64, n: f64, p: f64) -> Result<f64, CalculatorError> {
+    check_binomial(n, p, "binomcdf")?;
+    let k = k.floor();
+    if k < 0.0 {
+        Ok(0.0)
+    } else if k >= n {
+        Ok(1.0)
+    } else if p == 0.0 || p == 1.0 {
+        Ok(1.0 - p)
+    } else if n <= EXACT_TRIALS {
+        (0..=k as i64).try_fold(0.0, |sum, i| Ok(sum + binomial_pdf(i as f64, n, p)?))
+    } else {
+        beta_regularized(n - k, k + 1.0, 1.0 - p)
+    }
+}
+
+pub fn binomial_inv(q: f64, n: f64, p: f64) -> Result<f64, CalculatorError> {
+    check_probability(q, "binominv")?;
+    check_binomial(n, p, "binominv")?;
+    smallest_reaching(q, 0.0, n, |k| binomial_cdf(k, n, p))
+}
+
+pub fn poisson_pdf(k: f64, mean: f64) -> Result<f64, CalculatorError> {
+    if mean <= 0.0 {
+        return Err(domain_error("poisspdf"));
+    }
+    if k < 0.0 || k.fract() != 0.0 {
+        return Ok(0.0);
+    }
+    Ok((k * mean.ln() - mean - lgamma(k + 1.0)?).exp())
+}
+
+pub fn poisson_cdf(k: f64, mean: f64) -> Result<f64, CalculatorError> {
+    if mean <= 0.0 {
+        return Err(domain_error("poisscdf"));
+    }
+    let k = k.floor();
+    if k < 0.0 {
+        Ok(0.0)
+    } else {
+        gamma_q(k + 1.0, mean)
+    }
+}
+
+pub fn poisson_inv(q: f64, mean: f64) -> Result<f64, CalculatorError> {
+    check_probability(q, "poissinv")?;
+    if mean <= 0.0 {
+        return Err(domain_error("poissinv"));
+    }
+    if q == 1.0 {
+        return Ok(f64::INFINITY);
+    }
+    // Double the upper bound until it's past q, the tail shrinks faster than exponentially
+    let mut high = mean.ceil().max(1.0);
+    while poisson_cdf(high, mean)? < q {
+        high *= 2.0;
+    }
+    smallest_reaching(q, 0.0, high, |k| poisson_cdf(k, mean))
+}
+
+// Smallest whole number from low to high where the cdf is at least q, by bisection
+fn smallest_reaching(
+    q: f64,
+    mut low: f64,
+    mut high: f64,
+    cdf: impl Fn(f64) -> Result<f64, CalculatorError>,
+) -> Result<f64, CalculatorError> {
+    if cdf(low)? >= q {
+        return Ok(low);
+    }
+    // cdf(low) < q <= cdf(high)
+    while high - low > 1.0 {
+        let middle = ((low + high) / 2.0).floor();
+        if cdf(middle)? >= q {
+            high = middle;
+        } else {
+            low = middle;
+        }
+    }
+    Ok(high)
+}
+
+pub fn uniform_pdf(x: f64, a: f64, b: f64) -> Result<f64, CalculatorError> {
+    if a >= b {
+        return Err(domain_error("unifpdf"));
+    }
+    Ok(if (a..=b).contains(&x) {
+        1.0 / (b - a)
+    } else {
+        0.0
+    })
+}
+
+pub fn uniform_cdf(x: f64, a: f64, b: f64) -> Result<f64, CalculatorError> {
+    if a >= b {
+        return Err(domain_error("unifcdf"));
+    }
+    Ok(((x - a) / (b - a)).clamp(0.0, 1.0))
+}
+
+pub fn uniform_inv(q: f64, a: f64, b: f64) -> Result<f64, CalculatorError> {
+    check_probability(q, "unifinv")?;
+    if a >= b {
+        return Err(domain_error("unifinv"));
+    }
+    Ok(a + q * (b - a))
+}
+
+pub fn exponential_pdf(x: f64, rate: f64) -> Result<f64, CalculatorError> {
+    if rate <= 0.0 {
+        return Err(domain_error("exppdf"));
+    }
+    Ok(if x < 0.0 {
+        0.0
+    } else {
+        rate * (-rate * x).exp()
+    })
+}
+
+pub fn exponential_cdf(x: f64, rate: f64) -> Result<f64, CalculatorError> {
+    if rate <= 0.0 {
+        return Err(domain_error("expcdf"));
+    }
+    Ok(if x < 0.0 { 0.0 } else { -(-rate * x).exp_m1() })
+}
+
+pub fn exponential_inv(q: f64, rate: f64) -> Result<f64, CalculatorError> {
+    check_probability(q, "expinv")?;
+    if rate <= 0.0 {
+        return Err(domain_error("expinv"));
+    }
+    Ok(-(-q).ln_1p() / rate)
+}
+
+pub fn t_pdf(x: f64, df: f64) -> Result<f64, CalculatorError> {
+    if df <= 0.0 {
+        return Err(domain_error("tpdf"));
+    }
+    let ln_scale = lgamma((df + 1.0) / 2.0)? - lgamma(df / 2.0)? - 0.5 * (df * PI).ln();
+    Ok((ln_scale - (df + 1.0) / 2.0 * (x * x / df).ln_1p()).exp())
+}
+
+pub fn t_cdf(x: f64, df: f64) -> Result<f64, CalculatorError> {
+    if df <= 0.0 {
+        return Err(domain_error("tcdf"));
+    }
+    if x.is_infinite() {
+        return Ok(if x > 0.0 { 1.0 } else { 0.0 });
+    }
+    // Near 0, df / (df + x²) rounds to 1 and loses x, so the probability of being closer to 0 than x
+    // is used instead of the tail
+    if x.abs() < 1.0 {
+        let middle = beta_regularized(0.5, df / 2.0, x * x / (df + x * x))? / 2.0;
+        return Ok(0.5 + middle.copysign(x));
+    }
+    // Probability of being further from 0 than x on one side
+    let tail = beta_regularized(df / 2.0, 0.5, df / (df + x * x))? / 2.0;
+    Ok(if x > 0.0 { 1.0 - tail } else { tail })
+}
+
+pub fn t_inv(q: f64, df: f64) -> Result<f64, CalculatorError> {
+    const MAX_ITERATIONS: usize = 200;
+    check_probability(q, "tinv")?;
+    if df <= 0.0 {
+        return Err(domain_error("tinv"));
+    }
+    if q == 0.0 {
+        return Ok(f64::NEG_INFINITY);
+    }
+    if q == 1.0 {
+        return Ok(f64::INFINITY);
+    }
+    // Widen a bracket around the answer, then bisect it
+    let (mut low, mut high) = (-1.0, 1.0);
+    while t_cdf(low, df)? > q {
+        low *= 2.0;
+    }
+    while t_cdf(high, df)? < q {
+        high *= 2.0;
+    }
+    for _ in 0..MAX_ITERATIONS {
+        let middle = (low + high) / 2.0;
+        if middle == low || middle == high {
+            break;
+        }
+        let p = t_cdf(middle, df)?;
+        if p == q {
+            return Ok(middle);
+        }
+        if p < q {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    Ok((low + high) / 2.0)
+}
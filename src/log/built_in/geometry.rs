@@ -0,0 +1,78 @@
+use crate::calculator::CalculatorError;
+
+fn check_lengths(a: &[f64], b: &[f64]) -> Result<(), CalculatorError> {
+    if a.len() == b.len() {
+        Ok(())
+    } else {
+        Err(CalculatorError::ShapeMismatch(a.len(), b.len()))
+    }
+}
+
+pub fn dot(a: &[f64], b: &[f64]) -> Result<f64, CalculatorError> {
+    check_lengths(a, b)?;
+    Ok(a.iter().zip(b).map(|(x, y)| x * y).sum())
+}
+
+pub fn magnitude(v: &[f64]) -> f64 {
+    v.iter().fold(0.0, |acc, n| acc + n.powf(2.0)).sqrt()
+}
+
+// Only defined for 3D vectors
+pub fn cross(a: &[f64], b: &[f64]) -> Result<Vec<f64>, CalculatorError> {
+    match (a, b) {
+        ([ax, ay, az], [bx, by, bz]) => Ok(vec![
+            ay * bz - az * by,
+            az * bx - ax * bz,
+            ax * by - ay * bx,
+        ]),
+        _ => Err(CalculatorError::CrossNeeds3D),
+    }
+}
+
+pub fn normalize(v: &[f64]) -> Result<Vec<f64>, CalculatorError> {
+    let m = magnitude(v);
+    if m == 0.0 {
+        Err(CalculatorError::DomainError(String::from("normalize")))
+    } else {
+        Ok(v.iter().map(|n| n / m).collect())
+    }
+}
+
+// Angle between two vectors in radians
+pub fn angle(a: &[f64], b: &[f64]) -> Result<f64, CalculatorError> {
+    let lengths = magnitude(a) * magnitude(b);
+    if lengths == 0.0 {
+        return Err(CalculatorError::DomainError(String::from("angle")));
+    }
+    // Rounding can push the cosine just outside of [-1, 1]
+    Ok((dot(a, b)? / lengths).clamp(-1.0, 1.0).acos())
+}
+
+// Projection of a onto b
+pub fn project(a: &[f64], b: &[f64]) -> Result<Vec<f64>, CalculatorError> {
+    let b_squared = dot(b, b)?;
+    if b_squared == 0.0 {
+        return Err(CalculatorError::DomainError(String::from("proj")));
+    }
+    let scale = dot(a, b)? / b_squared;
+    Ok(b.iter().map(|n| n * scale).collect())
+}
+
+// Part of a perpendicular to b
+pub fn reject(a: &[f64], b: &[f64]) -> Result<Vec<f64>, CalculatorError> {
+    Ok(a.iter().zip(project(a, b)?).map(|(x, p)| x - p).collect())
+}
+
+// Reflects v off of a surface with the given normal
+pub fn reflect(v: &[f64], normal: &[f64]) -> Result<Vec<f64>, CalculatorError> {
+    let n = normalize(normal)?;
+    let d = 2.0 * dot(v, &n)?;
+    Ok(v.iter().zip(n).map(|(x, n)| x - d * n).collect())
+}
+
+pub fn distance(a: &[f64], b: &[f64]) -> Result<f64, CalculatorError> {
+    check_lengths(a, b)?;
+    Ok(magnitude(
+        &a.iter().zip(b).map(|(x, y)| x - y).collect::<Vec<f64>>(),
+    ))
+}
@@ -0,0 +1,73 @@
+use crate::calculator::num_types::{Function, NumType};
+use crate::calculator::{CalculatorError, Callable};
+use crate::log::Log;
+
+// Results are put into vectors, so they have to be plain numbers
+fn to_component(n: NumType) -> Result<f64, CalculatorError> {
+    match n {
+        NumType::Scalar(s) => Ok(s),
+        NumType::Vector(_) => Err(CalculatorError::RecursiveVectors),
+        other => Err(other.not_real()),
+    }
+}
+
+pub fn map(values: &[f64], f: &Function, log: &Log) -> Result<Vec<f64>, CalculatorError> {
+    let mut f = Callable::new(f, log);
+    values
+        .iter()
+        .map(|x| to_component(f.call(vec![NumType::Scalar(*x)])?))
+        .collect()
+}
+
+// Keeps the values for which f is anything but 0
+pub fn filter(values: &[f64], f: &Function, log: &Log) -> Result<Vec<f64>, CalculatorError> {
+    let mut f = Callable::new(f, log);
+    let mut kept = Vec::new();
+    for x in values {
+        if to_component(f.call(vec![NumType::Scalar(*x)])?)? != 0.0 {
+            kept.push(*x);
+        }
+    }
+    Ok(kept)
+}
+
+// Combines each value into the total from left to right with f(total, value)
+pub fn fold(
+    values: &[f64],
+    init: NumType,
+    f: &Function,
+    log: &Log,
+) -> Result<NumType, CalculatorError> {
+    let mut f = Callable::new(f, log);
+    values
+        .iter()
+        .try_fold(init, |total, x| f.call(vec![total, NumType::Scalar(*x)]))
+}
+
+// Like fold, starting from the first value
+pub fn reduce(values: &[f64], f: &Function, log: &Log) -> Result<NumType, CalculatorError> {
+    match values.split_first() {
+        Some((first, rest)) => fold(rest, NumType::Scalar(*first), f, log),
+        None => Err(CalculatorError::EmptyInput(String::from("reduce"))),
+    }
+}
+
+// Pairs up the values of a and b, giving f(a, b) for each pair
+pub fn zip(a: &[f64], b: &[f64], f: &Function, log: &Log) -> Result<Vec<f64>, CalculatorError> {
+    if a.len() != b.len() {
+        return Err(CalculatorError::ShapeMismatch(a.len(), b.len()));
+    }
+    let mut f = Callable::new(f, log);
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| to_component(f.call(vec![NumType::Scalar(*x), NumType::Scalar(*y)])?))
+        .collect()
+}
+
+// Sorts the values from smallest to largest f(value), keeping the order of values with the same key
+pub fn sort_by(values: &[f64], f: &Function, log: &Log) -> Result<Vec<f64>, CalculatorError> {
+    let keys = map(values, f, log)?;
+    let mut pairs: Vec<(f64, f64)> = keys.into_iter().zip(values.iter().copied()).collect();
+    pairs.sort_by(|a, b| a.0.total_cmp(&b.0));
+    Ok(pairs.into_iter().map(|(_, x)| x).collect())
+}
@@ -0,0 +1,216 @@
+use crate::calculator::CalculatorError;
+
+// Small primes used for trial division and as Miller-Rabin witnesses
+// Testing against all of these is deterministic for every 64 bit number
+const SMALL_PRIMES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+pub fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+pub fn lcm(a: u64, b: u64) -> Result<u64, CalculatorError> {
+    if a == 0 || b == 0 {
+        return Ok(0);
+    }
+    (a / gcd(a, b))
+        .checked_mul(b)
+        .ok_or(CalculatorError::BeyondExactIntegers(String::from("lcm")))
+}
+
+// Number of ways to choose r things from n, ignoring order
+pub fn ncr(n: i64, r: i64) -> Result<f64, CalculatorError> {
+    if n < 0 || r < 0 {
+        return Err(CalculatorError::DomainError(String::from("ncr")));
+    }
+    if r > n {
+        return Ok(0.0);
+    }
+    // Multiplying and dividing as we go keeps every step a whole number
+    let r = r.min(n - r);
+    let mut c: u128 = 1;
+    for i in 1..=r as u128 {
+        match c.checked_mul(n as u128 - r as u128 + i) {
+            Some(p) => c = p / i,
+            // Too big to be exact, fall back to the gamma function
+            None => {
+                return Ok((super::special::lgamma(n as f64 + 1.0)?
+                    - super::special::lgamma(r as f64 + 1.0)?
+                    - super::special::lgamma((n - r) as f64 + 1.0)?)
+                .exp()
+                .round())
+            }
+        }
+    }
+    Ok(c as f64)
+}
+
+// Number of ways to arrange r things out of n
+pub fn npr(n: i64, r: i64) -> Result<f64, CalculatorError> {
+    if n < 0 || r < 0 {
+        return Err(CalculatorError::DomainError(String::from("npr")));
+    }
+    if r > n {
+        return Ok(0.0);
+    }
+    Ok((n - r + 1..=n).fold(1.0, |acc, i| acc * i as f64))
+}
+
+fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+    ((a as u128 * b as u128) % m as u128) as u64
+}
+
+fn pow_mod(mut base: u64, mut exp: u64, m: u64) -> u64 {
+    let mut result = 1 % m;
+    base %= m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul_mod(result, base, m);
+        }
+        base = mul_mod(base, base, m);
+        exp >>= 1;
+    }
+    result
+}
+
+// Deterministic Miller-Rabin test
+pub fn is_prime(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    for p in SMALL_PRIMES {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+
+    let mut d = n - 1;
+    let mut s = 0;
+    while d.is_multiple_of(2) {
+        d /= 2;
+        s += 1;
+    }
+
+    'witness: for a in SMALL_PRIMES {
+        let mut x = pow_mod(a, d, n);
+        if x == 1 || x == n - 1 {
+            continue;
+        }
+        for _ in 1..s {
+            x = mul_mod(x, x, n);
+            if x == n - 1 {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+// Smallest prime bigger than n
+pub fn next_prime(n: i64) -> i64 {
+    let mut candidate = n.max(1) + 1;
+    while !is_prime(candidate as u64) {
+        candidate += 1;
+    }
+    candidate
+}
+
+// Finds some factor of a composite number with Pollard's rho algorithm
+fn pollard_rho(n: u64) -> u64 {
+    if n.is_multiple_of(2) {
+        return 2;
+    }
+    for c in 1.. {
+        let f = |x: u64| (mul_mod(x, x, n) + c) % n;
+        let (mut x, mut y, mut d) = (2, 2, 1);
+        while d == 1 {
+            x = f(x);
+            y = f(f(y));
+            d = gcd(x.abs_diff(y), n);
+        }
+        if d != n {
+            return d;
+        }
+    }
+    n
+}
+
+// Prime factors of n from smallest to largest, repeated as many times as they divide n
+pub fn factor(n: i64) -> Result<Vec<u64>, CalculatorError> {
+    if n < 1 {
+        return Err(CalculatorError::DomainError(String::from("factor")));
+    }
+
+    let mut factors = Vec::new();
+    let mut n = n as u64;
+    for p in SMALL_PRIMES {
+        while n.is_multiple_of(p) {
+            factors.push(p);
+            n /= p;
+        }
+    }
+
+    // Whatever is left only has big factors, split it up until everything is prime
+    let mut remaining = vec![n];
+    while let Some(m) = remaining.pop() {
+        if m == 1 {
+            continue;
+        } else if is_prime(m) {
+            factors.push(m);
+        } else {
+            let d = pollard_rho(m);
+            remaining.push(d);
+            remaining.push(m / d);
+        }
+    }
+
+    factors.sort_unstable();
+    Ok(factors)
+}
+
+// Euler's totient function, the count of numbers up to n which share no factors with it
+pub fn totient(n: i64) -> Result<i64, CalculatorError> {
+    if n < 1 {
+        return Err(CalculatorError::DomainError(String::from("totient")));
+    }
+    let mut factors = factor(n)?;
+    factors.dedup();
+    Ok(factors
+        .iter()
+        .fold(n, |acc, p| acc / *p as i64 * (*p as i64 - 1)))
+}
+
+// Inverse of a modulo m, using the extended Euclidean algorithm
+pub fn mod_inv(a: i64, m: i64) -> Result<i64, CalculatorError> {
+    if m < 1 {
+        return Err(CalculatorError::DomainError(String::from("modinv")));
+    }
+    let (mut old_r, mut r) = (a.rem_euclid(m) as i128, m as i128);
+    let (mut old_s, mut s) = (1i128, 0i128);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_s, s) = (s, old_s - q * s);
+    }
+    // There is no inverse unless a and m share no factors
+    if old_r != 1 {
+        return Err(CalculatorError::DomainError(String::from("modinv")));
+    }
+    Ok(old_s.rem_euclid(m as i128) as i64)
+}
+
+// base^exp modulo m, negative exponents use the modular inverse
+pub fn mod_pow(base: i64, exp: i64, m: i64) -> Result<i64, CalculatorError> {
+    if m < 1 {
+        return Err(CalculatorError::DomainError(String::from("modpow")));
+    }
+    let base = if exp < 0 {
+        mod_inv(base, m)?
+    } else {
+        base.rem_euclid(m)
+    };
+    Ok(pow_mod(base as u64, exp.unsigned_abs(), m as u64) as i64)
+}
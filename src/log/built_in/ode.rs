@@ -0,0 +1,194 @@
+use crate::calculator::num_types::NumType;
+use crate::calculator::{evaluate, CalculatorError};
+use crate::log::Log;
+
+// Most steps the integrator may take, so a stiff or exploding equation gives up instead of freezing
+const MAX_STEPS: usize = 100_000;
+// Errors allowed on each step, relative to the size of y and absolute for when y is near 0
+const RELATIVE_TOLERANCE: f64 = 1e-10;
+const ABSOLUTE_TOLERANCE: f64 = 1e-12;
+
+// Dormand-Prince coefficients, the last row gives the 5th order result
+const STAGES: [f64; 7] = [0.0, 1.0 / 5.0, 3.0 / 10.0, 4.0 / 5.0, 8.0 / 9.0, 1.0, 1.0];
+const WEIGHTS: [&[f64]; 7] = [
+    &[],
+    &[1.0 / 5.0],
+    &[3.0 / 40.0, 9.0 / 40.0],
+    &[44.0 / 45.0, -56.0 / 15.0, 32.0 / 9.0],
+    &[
+        19372.0 / 6561.0,
+        -25360.0 / 2187.0,
+        64448.0 / 6561.0,
+        -212.0 / 729.0,
+    ],
+    &[
+        9017.0 / 3168.0,
+        -355.0 / 33.0,
+        46732.0 / 5247.0,
+        49.0 / 176.0,
+        -5103.0 / 18656.0,
+    ],
+    &[
+        35.0 / 384.0,
+        0.0,
+        500.0 / 1113.0,
+        125.0 / 192.0,
+        -2187.0 / 6784.0,
+        11.0 / 84.0,
+    ],
+];
+// Difference between the 5th and embedded 4th order results, which estimates the error of a step
+const ERROR_WEIGHTS: [f64; 7] = [
+    71.0 / 57600.0,
+    0.0,
+    -71.0 / 16695.0,
+    71.0 / 1920.0,
+    -17253.0 / 339200.0,
+    22.0 / 525.0,
+    -1.0 / 40.0,
+];
+
+// dy/dt written in t and y, evaluated in a scoped copy of the log so t and y don't overwrite anything
+// y is a number, or a vector for a system of equations
+pub struct System<'a> {
+    body: &'a str,
+    name: &'a str,
+    scope: Log,
+    vector: bool,
+}
+
+impl<'a> System<'a> {
+    // The system along with the components of the starting value
+    pub fn new(
+        body: &'a str,
+        y0: &NumType,
+        log: &Log,
+        name: &'a str,
+    ) -> Result<(Self, Vec<f64>), CalculatorError> {
+        let (y0, vector) = match y0 {
+            NumType::Scalar(y) => (vec![*y], false),
+            NumType::Vector(v) => (v.clone(), true),
+            other => return Err(other.not_real()),
+        };
+        let system = System {
+            body,
+            name,
+            scope: log.scope()?,
+            vector,
+        };
+        Ok((system, y0))
+    }
+
+    // Puts the components back into the same shape as the starting value
+    pub fn to_num(&self, y: Vec<f64>) -> NumType {
+        if self.vector {
+            NumType::Vector(y)
+        } else {
+            NumType::Scalar(y[0])
+        }
+    }
+
+    fn slope(&mut self, t: f64, y: &[f64]) -> Result<Vec<f64>, CalculatorError> {
+        self.scope.add_var(String::from("t"), &NumType::Scalar(t));
+        self.scope
+            .add_var(String::from("y"), &self.to_num(y.to_vec()));
+        match evaluate(self.body, &self.scope)? {
+            NumType::Scalar(s) if !self.vector => Ok(vec![s]),
+            NumType::Vector(v) if self.vector && v.len() == y.len() => Ok(v),
+            NumType::Vector(v) if self.vector => {
+                Err(CalculatorError::ShapeMismatch(y.len(), v.len()))
+            }
+            _ => Err(CalculatorError::MissingFunctionParameters(
+                self.name.to_owned(),
+            )),
+        }
+    }
+}
+
+// Values of y at evenly spaced times from t0 to t_end, the first being y0
+// Steps grow and shrink to keep the error of each one within the tolerances
+pub fn solve(
+    system: &mut System,
+    t0: f64,
+    y0: Vec<f64>,
+    t_end: f64,
+    samples: usize,
+) -> Result<Vec<Vec<f64>>, CalculatorError> {
+    if !t0.is_finite() || !t_end.is_finite() || y0.iter().any(|y| !y.is_finite()) {
+        return Err(CalculatorError::DomainError(system.name.to_owned()));
+    }
+    let mut path = vec![y0];
+    let mut h = (t_end - t0) / 100.0;
+    let mut steps = 0;
+    for i in 1..=samples {
+        let from = t0 + (t_end - t0) * (i - 1) as f64 / samples as f64;
+        let to = t0 + (t_end - t0) * i as f64 / samples as f64;
+        let y = integrate(system, from, path[i - 1].clone(), to, &mut h, &mut steps)?;
+        path.push(y);
+    }
+    Ok(path)
+}
+
+// Steps from t to t_end, carrying the step size and count over between calls
+fn integrate(
+    system: &mut System,
+    mut t: f64,
+    mut y: Vec<f64>,
+    t_end: f64,
+    h: &mut f64,
+    steps: &mut usize,
+) -> Result<Vec<f64>, CalculatorError> {
+    while t != t_end {
+        *steps += 1;
+        // Steps which can't move t any more mean the solution is blowing up (y' = y^2 past t = 1)
+        if *steps > MAX_STEPS || t + *h == t {
+            return Err(CalculatorError::ConvergenceFailure(system.name.to_owned()));
+        }
+        // The last step lands exactly on the end
+        let last = h.abs() >= (t_end - t).abs();
+        let step = if last { t_end - t } else { *h };
+
+        let mut k: Vec<Vec<f64>> = Vec::with_capacity(STAGES.len());
+        for (c, weights) in STAGES.iter().zip(WEIGHTS) {
+            let y_stage = combine(&y, &k, weights, step);
+            k.push(system.slope(t + c * step, &y_stage)?);
+        }
+        let y_new = combine(&y, &k, WEIGHTS[6], step);
+        let error_estimate = combine(&vec![0.0; y.len()], &k, &ERROR_WEIGHTS, step);
+
+        // Largest error compared to what's allowed, anything up to 1 is accepted
+        let error = y
+            .iter()
+            .zip(&y_new)
+            .zip(&error_estimate)
+            .map(|((y, y_new), e)| {
+                e.abs() / (ABSOLUTE_TOLERANCE + RELATIVE_TOLERANCE * y.abs().max(y_new.abs()))
+            })
+            .fold(0.0, f64::max);
+        // max skips NaN, so a step which leaves the real numbers is rejected separately
+        let error = if y_new.iter().all(|y| y.is_finite()) {
+            error
+        } else {
+            f64::INFINITY
+        };
+        if error <= 1.0 {
+            t = if last { t_end } else { t + step };
+            y = y_new;
+        }
+        // The error of a 5th order step grows with h^5
+        let factor = if error == 0.0 {
+            5.0
+        } else {
+            (0.9 * error.powf(-0.2)).clamp(0.2, 5.0)
+        };
+        *h = step * factor;
+    }
+    Ok(y)
+}
+
+// y + step * sum of weight * k for each stage
+fn combine(y: &[f64], k: &[Vec<f64>], weights: &[f64], step: f64) -> Vec<f64> {
+    (0..y.len())
+        .map(|i| y[i] + step * k.iter().zip(weights).map(|(k, w)| w * k[i]).sum::<f64>())
+        .collect()
+}
@@ -0,0 +1,245 @@
+use crate::calculator::num_types::Complex;
+use crate::calculator::CalculatorError;
+
+// Polynomials are vectors of coefficients from the highest power down, [1, 0, -4] is x^2 - 4
+
+// Leading zeros don't change the polynomial but would make it look like a higher degree
+fn trim(p: &[f64]) -> &[f64] {
+    let start = p.iter().position(|c| *c != 0.0).unwrap_or(p.len());
+    &p[start..]
+}
+
+// Horner's method
+pub fn eval(p: &[f64], x: f64) -> f64 {
+    p.iter().fold(0.0, |total, c| total * x + c)
+}
+
+fn eval_complex(p: &[f64], z: Complex) -> Complex {
+    p.iter().fold(Complex::new(0.0, 0.0), |total, c| {
+        total * z + Complex::new(*c, 0.0)
+    })
+}
+
+pub fn derivative(p: &[f64]) -> Vec<f64> {
+    let p = trim(p);
+    let degree = p.len().saturating_sub(1);
+    p[..degree]
+        .iter()
+        .enumerate()
+        .map(|(i, c)| c * (degree - i) as f64)
+        .collect()
+}
+
+// The antiderivative which is equal to constant at 0
+pub fn integral(p: &[f64], constant: f64) -> Vec<f64> {
+    let p = trim(p);
+    let degree = p.len();
+    p.iter()
+        .enumerate()
+        .map(|(i, c)| c / (degree - i) as f64)
+        .chain(std::iter::once(constant))
+        .collect()
+}
+
+pub fn multiply(p: &[f64], q: &[f64]) -> Vec<f64> {
+    if p.is_empty() || q.is_empty() {
+        return Vec::new();
+    }
+    let mut product = vec![0.0; p.len() + q.len() - 1];
+    for (i, a) in p.iter().enumerate() {
+        for (j, b) in q.iter().enumerate() {
+            product[i + j] += a * b;
+        }
+    }
+    product
+}
+
+// Every root, complex ones included, using the Durand-Kerner method which improves guesses for all
+// of the roots at once
+pub fn roots(p: &[f64]) -> Result<Vec<Complex>, CalculatorError> {
+    const MAX_ITERATIONS: usize = 1000;
+    let p = trim(p);
+    if p.is_empty() {
+        // Every number is a root of 0
+        return Err(CalculatorError::DomainError(String::from("polyroots")));
+    }
+    // Dividing by the leading coefficient doesn't change the roots
+    let monic: Vec<f64> = p.iter().map(|c| c / p[0]).collect();
+    let degree = monic.len() - 1;
+
+    // Powers of a complex number which isn't a root of unity are the usual starting guesses
+    let seed = Complex::new(0.4, 0.9);
+    let mut guesses: Vec<Complex> =
+        std::iter::successors(Some(Complex::new(1.0, 0.0)), |z| Some(*z * seed))
+            .take(degree)
+            .collect();
+
+    for _ in 0..MAX_ITERATIONS {
+        let mut largest_change: f64 = 0.0;
+        for i in 0..degree {
+            let z = guesses[i];
+            let denominator = (0..degree)
+                .filter(|j| *j != i)
+                .fold(Complex::new(1.0, 0.0), |total, j| total * (z - guesses[j]));
+            let change = eval_complex(&monic, z) / denominator;
+            if change.re.is_finite() && change.im.is_finite() {
+                guesses[i] = z - change;
+                largest_change = largest_change.max(change.abs() / z.abs().max(1.0));
+            }
+        }
+        // Repeated roots converge slowly and stall well before the steps get tiny, so guesses which
+        // are as close to roots as rounding allows are accepted too
+        if largest_change <= 1e-14 || guesses.iter().all(|z| is_rounding_residual(&monic, *z)) {
+            return Ok(tidy_roots(cluster_roots(&monic, guesses)));
+        }
+    }
+    Err(CalculatorError::ConvergenceFailure(String::from(
+        "polyroots",
+    )))
+}
+
+// Whether p(z) is no bigger than the rounding errors Horner's method makes evaluating it
+fn is_rounding_residual(p: &[f64], z: Complex) -> bool {
+    let bound = p.iter().fold(0.0, |total, c| total * z.abs() + c.abs());
+    eval_complex(p, z).abs() <= 8.0 * p.len() as f64 * f64::EPSILON * bound
+}
+
+// A root repeated m times only comes out to about the m-th root of the rounding error, scattered
+// around the true root (within 1e-4 of 1 for (x - 1)^4). Groups spread no wider than that are
+// replaced by one root, found from their average
+fn cluster_roots(p: &[f64], roots: Vec<Complex>) -> Vec<Complex> {
+    let widest = roots.len();
+    let mut remaining = roots;
+    let mut clustered = Vec::with_capacity(remaining.len());
+    while let Some(z) = remaining.pop() {
+        // Everything close enough to z to be part of the widest possible cluster
+        let (mut group, rest): (Vec<Complex>, Vec<Complex>) = remaining
+            .into_iter()
+            .partition(|w| (*w - z).abs() <= repeated_root_spread(widest, z));
+        group.push(z);
+        remaining = rest;
+
+        let count = Complex::new(group.len() as f64, 0.0);
+        let mean = group
+            .iter()
+            .fold(Complex::new(0.0, 0.0), |total, w| total + *w)
+            / count;
+        let spread = repeated_root_spread(group.len(), mean);
+        if group.len() > 1 && group.iter().all(|w| (*w - mean).abs() <= spread) {
+            let root = polish_repeated_root(p, group.len(), mean);
+            clustered.extend(std::iter::repeat_n(root, group.len()));
+        } else {
+            clustered.extend(group);
+        }
+    }
+    clustered
+}
+
+// How far the guesses for a root repeated m times at z may be scattered by rounding
+fn repeated_root_spread(m: usize, z: Complex) -> f64 {
+    10.0 * f64::EPSILON.powf(1.0 / m as f64) * z.abs().max(1.0)
+}
+
+// A root repeated m times is a simple root of the (m - 1)th derivative, where Newton's method
+// converges quickly and accurately
+fn polish_repeated_root(p: &[f64], m: usize, guess: Complex) -> Complex {
+    const MAX_ITERATIONS: usize = 20;
+    let d = (1..m).fold(p.to_vec(), |d, _| derivative(&d));
+    let slope = derivative(&d);
+    let mut z = guess;
+    for _ in 0..MAX_ITERATIONS {
+        let step = eval_complex(&d, z) / eval_complex(&slope, z);
+        if !step.re.is_finite() || !step.im.is_finite() {
+            break;
+        }
+        z = z - step;
+        if step.abs() <= 1e-15 * z.abs().max(1.0) {
+            break;
+        }
+    }
+    z
+}
+
+// Rounding leaves tiny imaginary parts on real roots, which are removed so they print as real numbers.
+// "Tiny" is measured against the root itself, so roots near 0 keep their size
+fn tidy_roots(mut roots: Vec<Complex>) -> Vec<Complex> {
+    for z in roots.iter_mut() {
+        if z.im.abs() <= 1e-10 * z.abs() {
+            z.im = 0.0;
+        }
+        if z.re.abs() <= 1e-10 * z.abs() {
+            z.re = 0.0;
+        }
+    }
+    roots.sort_by(|a, b| a.re.total_cmp(&b.re).then(a.im.total_cmp(&b.im)));
+    roots
+}
+
+// Least squares solution of a x = b, through a QR decomposition made with Householder reflections
+// Each row of a is one equation, there must be at least as many equations as unknowns
+#[allow(clippy::needless_range_loop)] // Rows and columns are clearer as indices
+pub fn least_squares(
+    mut a: Vec<Vec<f64>>,
+    mut b: Vec<f64>,
+    name: &str,
+) -> Result<Vec<f64>, CalculatorError> {
+    let rows = a.len();
+    let columns = a.first().map_or(0, |r| r.len());
+    if rows < columns || columns == 0 {
+        return Err(CalculatorError::DomainError(name.to_owned()));
+    }
+
+    for k in 0..columns {
+        // Reflect column k so everything below the diagonal becomes 0
+        let norm = (k..rows).map(|i| a[i][k].powi(2)).sum::<f64>().sqrt();
+        if norm == 0.0 {
+            return Err(CalculatorError::DomainError(name.to_owned()));
+        }
+        let alpha = if a[k][k] > 0.0 { -norm } else { norm };
+        let mut v: Vec<f64> = (k..rows).map(|i| a[i][k]).collect();
+        v[0] -= alpha;
+        let v_norm = v.iter().map(|x| x * x).sum::<f64>();
+        if v_norm == 0.0 {
+            continue;
+        }
+        for j in k..columns {
+            let dot: f64 = (k..rows).map(|i| v[i - k] * a[i][j]).sum();
+            for i in k..rows {
+                a[i][j] -= 2.0 * dot / v_norm * v[i - k];
+            }
+        }
+        let dot: f64 = (k..rows).map(|i| v[i - k] * b[i]).sum();
+        for i in k..rows {
+            b[i] -= 2.0 * dot / v_norm * v[i - k];
+        }
+    }
+
+    // Back substitution through the upper triangle
+    let mut x = vec![0.0; columns];
+    for k in (0..columns).rev() {
+        let known: f64 = (k + 1..columns).map(|j| a[k][j] * x[j]).sum();
+        if a[k][k].abs() <= 1e-12 * a[0][0].abs() {
+            // The columns aren't independent, such as fitting a line through a single x value
+            return Err(CalculatorError::DomainError(name.to_owned()));
+        }
+        x[k] = (b[k] - known) / a[k][k];
+    }
+    Ok(x)
+}
+
+// Least squares polynomial of the given degree through the points
+pub fn fit(xs: &[f64], ys: &[f64], degree: usize) -> Result<Vec<f64>, CalculatorError> {
+    if xs.len() != ys.len() {
+        return Err(CalculatorError::ShapeMismatch(xs.len(), ys.len()));
+    }
+    let a = xs
+        .iter()
+        .map(|x| {
+            (0..=degree)
+                .rev()
+                .map(|power| x.powi(power as i32))
+                .collect()
+        })
+        .collect();
+    least_squares(a, ys.to_vec(), "polyfit")
+}
@@ -0,0 +1,79 @@
+use super::polynomial;
+use crate::calculator::CalculatorError;
+
+// Straight line through the points as [slope, intercept, r²]
+pub fn linear(xs: &[f64], ys: &[f64], name: &str) -> Result<Vec<f64>, CalculatorError> {
+    if xs.len() != ys.len() {
+        return Err(CalculatorError::ShapeMismatch(xs.len(), ys.len()));
+    }
+    let a = xs.iter().map(|x| vec![*x, 1.0]).collect();
+    let line = polynomial::least_squares(a, ys.to_vec(), name)?;
+    let (slope, intercept) = (line[0], line[1]);
+
+    // r² compares the leftover error with how much the ys vary on their own
+    let mean = ys.iter().sum::<f64>() / ys.len() as f64;
+    let total: f64 = ys.iter().map(|y| (y - mean).powi(2)).sum();
+    let residual: f64 = xs
+        .iter()
+        .zip(ys)
+        .map(|(x, y)| (y - (slope * x + intercept)).powi(2))
+        .sum();
+    // Points which all have the same y lie exactly on a flat line
+    let r2 = if total == 0.0 {
+        1.0
+    } else {
+        1.0 - residual / total
+    };
+    Ok(vec![slope, intercept, r2])
+}
+
+// y = a e^(b x) as [a, b, r²], fitted as a line through (x, ln y) so every y must be positive
+pub fn exponential(xs: &[f64], ys: &[f64]) -> Result<Vec<f64>, CalculatorError> {
+    if ys.iter().any(|y| *y <= 0.0) {
+        return Err(CalculatorError::DomainError(String::from("expfit")));
+    }
+    let ln_ys: Vec<f64> = ys.iter().map(|y| y.ln()).collect();
+    let line = linear(xs, &ln_ys, "expfit")?;
+    Ok(vec![line[1].exp(), line[0], line[2]])
+}
+
+// y = a ln(x) + b as [a, b, r²], fitted as a line through (ln x, y) so every x must be positive
+pub fn logarithmic(xs: &[f64], ys: &[f64]) -> Result<Vec<f64>, CalculatorError> {
+    if xs.iter().any(|x| *x <= 0.0) {
+        return Err(CalculatorError::DomainError(String::from("logfit")));
+    }
+    let ln_xs: Vec<f64> = xs.iter().map(|x| x.ln()).collect();
+    linear(&ln_xs, ys, "logfit")
+}
+
+// The points sorted by x, ready for interpolate
+// Each x may only appear once, otherwise there would be more than one y for it
+pub fn sorted_points(xs: &[f64], ys: &[f64]) -> Result<Vec<(f64, f64)>, CalculatorError> {
+    if xs.len() != ys.len() {
+        return Err(CalculatorError::ShapeMismatch(xs.len(), ys.len()));
+    }
+    if xs.is_empty() {
+        return Err(CalculatorError::EmptyInput(String::from("interp")));
+    }
+    let mut points: Vec<(f64, f64)> = xs.iter().copied().zip(ys.iter().copied()).collect();
+    points.sort_by(|a, b| a.0.total_cmp(&b.0));
+    if points.windows(2).any(|w| w[0].0 == w[1].0) {
+        return Err(CalculatorError::DomainError(String::from("interp")));
+    }
+    Ok(points)
+}
+
+// Linear interpolation between the points either side of x, which must be within the data
+pub fn interpolate(points: &[(f64, f64)], x: f64) -> Result<f64, CalculatorError> {
+    let (first, last) = (points[0], points[points.len() - 1]);
+    if !(first.0..=last.0).contains(&x) {
+        return Err(CalculatorError::DomainError(String::from("interp")));
+    }
+    if x == last.0 {
+        return Ok(last.1);
+    }
+    // Index of the first point past x, which is never the first point
+    let i = points.partition_point(|p| p.0 <= x);
+    let ((x0, y0), (x1, y1)) = (points[i - 1], points[i]);
+    Ok(y0 + (y1 - y0) * (x - x0) / (x1 - x0))
+}
@@ -0,0 +1,53 @@
+use crate::calculator::CalculatorError;
+
+// Longest vector a sequence may build, so a typo like 1..1e12 doesn't eat all the memory
+pub const MAX_LEN: usize = 1_000_000;
+
+fn check_len(len: f64) -> Result<usize, CalculatorError> {
+    if len <= MAX_LEN as f64 {
+        Ok(len as usize)
+    } else {
+        Err(CalculatorError::VectorTooLong(MAX_LEN))
+    }
+}
+
+// Every value from start to end inclusive, counting by step
+// Without a step it counts by 1 towards end, so 3..1 = [3, 2, 1]
+pub fn range(start: f64, end: f64, step: Option<f64>) -> Result<Vec<f64>, CalculatorError> {
+    let step = step.unwrap_or(if end < start { -1.0 } else { 1.0 });
+    if step == 0.0 || !step.is_finite() || !start.is_finite() || !end.is_finite() {
+        return Err(CalculatorError::DomainError(String::from("step")));
+    }
+    // Stepping away from the end gives nothing
+    let steps = (end - start) / step;
+    if steps < 0.0 {
+        return Ok(Vec::new());
+    }
+    // A little slack so 0..1 step 0.1 still reaches 1 despite rounding
+    let len = check_len((steps + 1e-9).floor() + 1.0)?;
+    Ok((0..len).map(|i| start + i as f64 * step).collect())
+}
+
+// n evenly spaced values from a to b, including both ends
+pub fn linspace(a: f64, b: f64, n: i64) -> Result<Vec<f64>, CalculatorError> {
+    if n < 0 {
+        return Err(CalculatorError::DomainError(String::from("linspace")));
+    }
+    let len = check_len(n as f64)?;
+    if len == 1 {
+        return Ok(vec![a]);
+    }
+    let step = (b - a) / (len as f64 - 1.0);
+    Ok((0..len)
+        .map(|i| if i == len - 1 { b } else { a + i as f64 * step })
+        .collect())
+}
+
+// The values repeated n times one after another
+pub fn repeat(values: &[f64], n: i64) -> Result<Vec<f64>, CalculatorError> {
+    if n < 0 {
+        return Err(CalculatorError::DomainError(String::from("repeat")));
+    }
+    check_len(values.len() as f64 * n as f64)?;
+    Ok(values.repeat(n as usize))
+}
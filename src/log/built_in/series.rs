@@ -0,0 +1,62 @@
+use crate::calculator::num_types::{to_integer, NumType};
+use crate::calculator::{evaluate, CalculatorError};
+use crate::log::Log;
+
+// Most terms a series may add up, so a typo like sum(k, 1, 10000000000, k) doesn't freeze the calculator
+const MAX_TERMS: i64 = 1_000_000;
+
+// The variable, bounds and expression of a series such as sum(k, 1, 100, k^2)
+pub struct Series<'a> {
+    var: &'a str,
+    from: i64,
+    to: i64,
+    body: &'a str,
+}
+
+// Checks whether the parameters describe a series: four of them, where the first is a name which
+// could be used as a variable. The variable only exists inside the series, hiding any other k, so
+// what's already defined never changes which form is used
+pub fn params<'a>(
+    args: &'a [String],
+    log: &Log,
+    name: &str,
+) -> Result<Option<Series<'a>>, CalculatorError> {
+    let [var, from, to, body] = args else {
+        return Ok(None);
+    };
+    let var = var.trim();
+    if log.validate_name(var).is_err() {
+        return Ok(None);
+    }
+    let bound = |s: &str| match evaluate(s, log)? {
+        NumType::Scalar(n) => to_integer(n, name),
+        _ => Err(CalculatorError::MissingFunctionParameters(name.to_owned())),
+    };
+    Ok(Some(Series {
+        var,
+        from: bound(from)?,
+        to: bound(to)?,
+        body,
+    }))
+}
+
+// Evaluates the expression for each whole number from the start to the end, combining the terms with op
+// A series which ends before it starts is empty, giving just the starting total
+pub fn fold(
+    series: Series,
+    log: &Log,
+    total: NumType,
+    name: &str,
+    op: fn(NumType, NumType) -> Result<NumType, CalculatorError>,
+) -> Result<NumType, CalculatorError> {
+    if series.to.saturating_sub(series.from) >= MAX_TERMS {
+        return Err(CalculatorError::DomainError(name.to_owned()));
+    }
+    let mut scope = log.scope()?;
+    let mut total = total;
+    for k in series.from..=series.to {
+        scope.add_var(series.var.to_owned(), &NumType::Scalar(k as f64));
+        total = op(total, evaluate(series.body, &scope)?)?;
+    }
+    Ok(total)
+}
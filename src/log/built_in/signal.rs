@@ -0,0 +1,178 @@
+use crate::calculator::num_types::Complex;
+use crate::calculator::CalculatorError;
+use std::f64::consts::PI;
+
+// Above this many multiplications, convolutions go through the FFT instead of being done directly
+const DIRECT_CONVOLUTION_LIMIT: usize = 10_000_000;
+
+// Discrete Fourier transform, X_k = sum x_j e^(-2 pi i jk / n), or the inverse with the sign flipped
+// and divided by n. Any length works, not just powers of 2
+pub fn fft(x: &[Complex], inverse: bool) -> Vec<Complex> {
+    let n = x.len();
+    let mut result = if n.is_power_of_two() {
+        let mut x = x.to_vec();
+        radix2(&mut x, inverse);
+        x
+    } else {
+        bluestein(x, inverse)
+    };
+    if inverse {
+        for z in result.iter_mut() {
+            *z = Complex::new(z.re / n as f64, z.im / n as f64);
+        }
+    }
+    tidy(result)
+}
+
+// Cooley-Tukey FFT done in place, the length must be a power of 2
+fn radix2(x: &mut [Complex], inverse: bool) {
+    let n = x.len();
+    // Put the values in bit reversed order, so each pass combines neighbouring halves
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            x.swap(i, j);
+        }
+    }
+
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut len = 2;
+    while len <= n {
+        let step = Complex::from_angle(sign * 2.0 * PI / len as f64);
+        for start in (0..n).step_by(len) {
+            let mut w = Complex::new(1.0, 0.0);
+            for k in 0..len / 2 {
+                let (a, b) = (x[start + k], x[start + k + len / 2] * w);
+                x[start + k] = a + b;
+                x[start + k + len / 2] = a - b;
+                w = w * step;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+// Bluestein's algorithm, which turns a transform of any length into a convolution
+// that can be done with power of 2 FFTs
+fn bluestein(x: &[Complex], inverse: bool) -> Vec<Complex> {
+    let n = x.len();
+    let m = (2 * n - 1).next_power_of_two();
+    let sign = if inverse { 1.0 } else { -1.0 };
+    // e^(-pi i k^2 / n), with k^2 taken mod 2n so the angle stays small and accurate
+    let chirp: Vec<Complex> = (0..n)
+        .map(|k| Complex::from_angle(sign * PI * ((k * k) % (2 * n)) as f64 / n as f64))
+        .collect();
+
+    let mut a = vec![Complex::new(0.0, 0.0); m];
+    for k in 0..n {
+        a[k] = x[k] * chirp[k];
+    }
+    let mut b = vec![Complex::new(0.0, 0.0); m];
+    b[0] = chirp[0].conj();
+    for k in 1..n {
+        b[k] = chirp[k].conj();
+        b[m - k] = chirp[k].conj();
+    }
+
+    radix2(&mut a, false);
+    radix2(&mut b, false);
+    let mut c: Vec<Complex> = a.iter().zip(&b).map(|(a, b)| *a * *b).collect();
+    radix2(&mut c, true);
+    (0..n)
+        .map(|k| {
+            let z = c[k] * chirp[k];
+            Complex::new(z.re / m as f64, z.im / m as f64)
+        })
+        .collect()
+}
+
+// Rounding leaves tiny parts which should be 0, compared to the largest value in the result
+fn tidy(mut x: Vec<Complex>) -> Vec<Complex> {
+    let largest = x.iter().map(|z| z.abs()).fold(0.0, f64::max);
+    for z in x.iter_mut() {
+        if z.re.abs() <= 1e-12 * largest {
+            z.re = 0.0;
+        }
+        if z.im.abs() <= 1e-12 * largest {
+            z.im = 0.0;
+        }
+    }
+    x
+}
+
+// Full convolution, every product of a and b added up by the sum of their positions
+pub fn convolve(a: &[f64], b: &[f64]) -> Vec<f64> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let len = a.len() + b.len() - 1;
+    if a.len().saturating_mul(b.len()) <= DIRECT_CONVOLUTION_LIMIT {
+        let mut result = vec![0.0; len];
+        for (i, x) in a.iter().enumerate() {
+            for (j, y) in b.iter().enumerate() {
+                result[i + j] += x * y;
+            }
+        }
+        return result;
+    }
+    // Multiplying the transforms convolves the signals
+    let padded = |v: &[f64]| {
+        let mut z: Vec<Complex> = v.iter().map(|x| Complex::new(*x, 0.0)).collect();
+        z.resize(len.next_power_of_two(), Complex::new(0.0, 0.0));
+        radix2(&mut z, false);
+        z
+    };
+    let mut c: Vec<Complex> = padded(a)
+        .iter()
+        .zip(&padded(b))
+        .map(|(a, b)| *a * *b)
+        .collect();
+    radix2(&mut c, true);
+    c[..len].iter().map(|z| z.re / c.len() as f64).collect()
+}
+
+pub fn cumulative_sum(v: &[f64]) -> Vec<f64> {
+    v.iter()
+        .scan(0.0, |total, x| {
+            *total += x;
+            Some(*total)
+        })
+        .collect()
+}
+
+// Differences between neighbouring values, one shorter than v
+pub fn differences(v: &[f64]) -> Vec<f64> {
+    v.windows(2).map(|w| w[1] - w[0]).collect()
+}
+
+// Mean of every run of width values in a row, only where the whole window fits
+pub fn moving_average(v: &[f64], width: usize) -> Result<Vec<f64>, CalculatorError> {
+    if width == 0 || width > v.len() {
+        return Err(CalculatorError::DomainError(String::from("movavg")));
+    }
+    Ok(v.windows(width)
+        .map(|w| w.iter().sum::<f64>() / width as f64)
+        .collect())
+}
+
+// Frequency of each value given by fft for n samples taken at sample_rate per second
+// Positive frequencies come first, then the negative ones, counting up to 0
+pub fn frequencies(n: usize, sample_rate: f64) -> Vec<f64> {
+    let positive = (n - 1) / 2 + 1;
+    (0..n)
+        .map(|k| {
+            let k = if k < positive {
+                k as f64
+            } else {
+                k as f64 - n as f64
+            };
+            k * sample_rate / n as f64
+        })
+        .collect()
+}
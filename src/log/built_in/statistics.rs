@@ -0,0 +1,81 @@
+use crate::calculator::CalculatorError;
+
+// Every function here is given at least one number
+
+pub fn sum(data: &[f64]) -> f64 {
+    data.iter().sum()
+}
+
+pub fn product(data: &[f64]) -> f64 {
+    data.iter().product()
+}
+
+pub fn mean(data: &[f64]) -> f64 {
+    sum(data) / data.len() as f64
+}
+
+pub fn min(data: &[f64]) -> f64 {
+    data.iter().cloned().fold(f64::INFINITY, f64::min)
+}
+
+pub fn max(data: &[f64]) -> f64 {
+    data.iter().cloned().fold(f64::NEG_INFINITY, f64::max)
+}
+
+fn sorted(data: &[f64]) -> Vec<f64> {
+    let mut sorted = data.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    sorted
+}
+
+pub fn median(data: &[f64]) -> f64 {
+    // The 50th percentile averages the middle two when there are an even number of values
+    percentile(data, 50.0).unwrap_or(f64::NAN)
+}
+
+// Most common value, ties go to the smallest
+pub fn mode(data: &[f64]) -> f64 {
+    let sorted = sorted(data);
+    let mut best = (sorted[0], 0);
+    let mut i = 0;
+    while i < sorted.len() {
+        let run = sorted[i..].iter().take_while(|n| **n == sorted[i]).count();
+        if run > best.1 {
+            best = (sorted[i], run);
+        }
+        i += run;
+    }
+    best.0
+}
+
+// Sample variance divides by n - 1, population variance by n
+pub fn variance(data: &[f64], sample: bool, name: &str) -> Result<f64, CalculatorError> {
+    let n = data.len() as f64;
+    if sample && data.len() < 2 {
+        return Err(CalculatorError::DomainError(name.to_owned()));
+    }
+    let mean = mean(data);
+    let squares: f64 = data.iter().map(|x| (x - mean).powi(2)).sum();
+    Ok(squares / if sample { n - 1.0 } else { n })
+}
+
+// Linearly interpolates between the closest ranks, p goes from 0 to 100
+pub fn percentile(data: &[f64], p: f64) -> Result<f64, CalculatorError> {
+    if !(0.0..=100.0).contains(&p) {
+        return Err(CalculatorError::DomainError(String::from("percentile")));
+    }
+    let sorted = sorted(data);
+    let rank = p / 100.0 * (sorted.len() - 1) as f64;
+    let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+    Ok(sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64))
+}
+
+// How many sample standard deviations x is from the mean of the data
+// Data which is all the same has no spread to measure against
+pub fn zscore(x: f64, data: &[f64]) -> Result<f64, CalculatorError> {
+    let deviation = variance(data, true, "zscore")?.sqrt();
+    if deviation == 0.0 {
+        return Err(CalculatorError::DomainError(String::from("zscore")));
+    }
+    Ok((x - mean(data)) / deviation)
+}
@@ -0,0 +1,236 @@
+use std::fmt::Display;
+
+use crate::calculator::{num_types::NumType, BitwiseOp, CalculatorError};
+
+// What happens when a result doesn't fit in the integer type
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Overflow {
+    Wrapping,
+    Checked,
+}
+
+// Fixed width integer type used in programmer mode, results are truncated to whole numbers and made to fit
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IntMode {
+    pub bits: u32,
+    pub signed: bool,
+    pub overflow: Overflow,
+}
+
+impl IntMode {
+    // Reads a type name such as u8 or i64
+    pub fn from_type_name(name: &str, overflow: Overflow) -> Option<IntMode> {
+        let signed = match name.chars().next()? {
+            'u' => false,
+            'i' => true,
+            _ => return None,
+        };
+        let bits: u32 = name[1..].parse().ok()?;
+        if [8, 16, 32, 64].contains(&bits) {
+            Some(IntMode {
+                bits,
+                signed,
+                overflow,
+            })
+        } else {
+            None
+        }
+    }
+
+    // Smallest and largest values of the integer type
+    fn range(self) -> (i128, i128) {
+        if self.signed {
+            (-(1 << (self.bits - 1)), (1 << (self.bits - 1)) - 1)
+        } else {
+            (0, (1 << self.bits) - 1)
+        }
+    }
+
+    pub fn apply_f64(self, n: f64) -> Result<f64, CalculatorError> {
+        if !n.is_finite() {
+            return Err(self.overflow_error());
+        }
+        Ok(self.fit(n.trunc() as i128)? as f64)
+    }
+
+    // Makes a whole number fit the type, by wrapping it around or giving an error
+    fn fit(self, n: i128) -> Result<i128, CalculatorError> {
+        let (min, max) = self.range();
+        if (min..=max).contains(&n) {
+            return Ok(n);
+        }
+
+        match self.overflow {
+            Overflow::Checked => Err(self.overflow_error()),
+            Overflow::Wrapping => Ok(self.wrap(n)),
+        }
+    }
+
+    // Keeps the low bits, then reinterprets them as signed if needed
+    fn wrap(self, n: i128) -> i128 {
+        let wrapped = n & ((1 << self.bits) - 1);
+        if self.signed && wrapped > self.range().1 {
+            wrapped - (1 << self.bits)
+        } else {
+            wrapped
+        }
+    }
+
+    fn overflow_error(self) -> CalculatorError {
+        CalculatorError::IntegerOverflow(self.to_string())
+    }
+
+    pub fn apply(self, n: NumType) -> Result<NumType, CalculatorError> {
+        match n {
+            // Functions aren't numbers, their results are kept in range when they are called
+            // Complex numbers only come from functions which are meant to give fractions anyway
+            NumType::Function(_) | NumType::Complex(_) => Ok(n),
+            NumType::Integer(i, _) => Ok(NumType::Integer(self.fit(i)?, self)),
+            NumType::Scalar(f) => Ok(NumType::Integer(self.apply_f64(f)? as i128, self)),
+            NumType::Vector(_) => n.try_map(|f| self.apply_f64(f)),
+        }
+    }
+
+    // A number typed in programmer mode. Checked types leave one which doesn't fit for whatever uses
+    // it to catch, so -128 still works in i8
+    pub fn literal(self, n: u64) -> NumType {
+        NumType::Integer(self.fit(n as i128).unwrap_or(n as i128), self)
+    }
+
+    // Values from variables and functions are used as integers of this type when they are whole
+    pub fn adopt(self, n: NumType) -> NumType {
+        match n {
+            NumType::Integer(i, _) => NumType::Integer(i, self),
+            NumType::Scalar(f) if f.fract() == 0.0 && f.abs() < u64::MAX as f64 => {
+                NumType::Integer(f as i128, self)
+            }
+            other => other,
+        }
+    }
+
+    // Integer operators are worked out in i128, where two values of the type only overflow through
+    // * and ^. The bits which do fit are still right, so wrapping types keep those
+    fn fit_either(self, checked: Option<i128>, wrapping: i128) -> Result<i128, CalculatorError> {
+        match (checked, self.overflow) {
+            (Some(n), _) => self.fit(n),
+            (None, Overflow::Wrapping) => self.fit(wrapping),
+            (None, Overflow::Checked) => Err(self.overflow_error()),
+        }
+    }
+
+    pub fn add(self, l: i128, r: i128) -> Result<i128, CalculatorError> {
+        self.fit_either(l.checked_add(r), l.wrapping_add(r))
+    }
+
+    pub fn multiply(self, l: i128, r: i128) -> Result<i128, CalculatorError> {
+        self.fit_either(l.checked_mul(r), l.wrapping_mul(r))
+    }
+
+    pub fn negate(self, n: i128) -> Result<i128, CalculatorError> {
+        self.fit_either(n.checked_neg(), n.wrapping_neg())
+    }
+
+    // Division drops the fraction, like it does for every other result in programmer mode
+    pub fn divide(self, l: i128, r: i128) -> Result<i128, CalculatorError> {
+        if r == 0 {
+            return Err(CalculatorError::DivisionByZero);
+        }
+        self.fit(l / r)
+    }
+
+    pub fn floor_divide(self, l: i128, r: i128) -> Result<i128, CalculatorError> {
+        if r == 0 {
+            return Err(CalculatorError::DivisionByZero);
+        }
+        let q = l / r;
+        self.fit(if l % r != 0 && (l < 0) != (r < 0) {
+            q - 1
+        } else {
+            q
+        })
+    }
+
+    // Remainder of floor division, which has the same sign as the divisor
+    pub fn modulo(self, l: i128, r: i128) -> Result<i128, CalculatorError> {
+        if r == 0 {
+            return Err(CalculatorError::DivisionByZero);
+        }
+        let m = l % r;
+        self.fit(if m != 0 && (m < 0) != (r < 0) {
+            m + r
+        } else {
+            m
+        })
+    }
+
+    pub fn pow(self, base: i128, exp: i128) -> Result<i128, CalculatorError> {
+        if exp < 0 {
+            // 1 / base^n, which only has a whole part for 1 and -1
+            return match base {
+                0 => Err(CalculatorError::DivisionByZero),
+                1 => Ok(1),
+                -1 => Ok(if exp % 2 == 0 { 1 } else { -1 }),
+                _ => Ok(0),
+            };
+        }
+        // Squaring and multiplying keeps the low bits right however big the exponent is
+        let (mut wrapping, mut square, mut e) = (1i128, base, exp);
+        while e > 0 {
+            if e & 1 == 1 {
+                wrapping = wrapping.wrapping_mul(square);
+            }
+            square = square.wrapping_mul(square);
+            e >>= 1;
+        }
+        let checked = if base.abs() <= 1 {
+            Some(wrapping)
+        } else {
+            u32::try_from(exp).ok().and_then(|e| base.checked_pow(e))
+        };
+        self.fit_either(checked, wrapping)
+    }
+
+    pub fn bitwise(self, op: BitwiseOp, l: i128, r: i128) -> Result<i128, CalculatorError> {
+        match op {
+            BitwiseOp::And => self.fit(l & r),
+            BitwiseOp::Or => self.fit(l | r),
+            BitwiseOp::Xor => self.fit(l ^ r),
+            BitwiseOp::ShiftLeft | BitwiseOp::ShiftRight => {
+                let shift = u32::try_from(r)
+                    .ok()
+                    .filter(|r| *r < u64::BITS)
+                    .ok_or(CalculatorError::IntegerOverflow(String::from("shift")))?;
+                if op == BitwiseOp::ShiftLeft {
+                    self.fit_either(l.checked_mul(1 << shift), l.wrapping_shl(shift))
+                } else {
+                    self.fit(l >> shift)
+                }
+            }
+        }
+    }
+
+    // Flipping every bit of the type never overflows it, whatever the overflow setting
+    pub fn not(self, n: i128) -> i128 {
+        self.wrap(!n)
+    }
+
+    // The bits of a value as stored in this type, so negative numbers show as two's complement
+    pub fn to_bits(self, n: i128) -> u64 {
+        (n & ((1 << self.bits) - 1)) as u64
+    }
+}
+
+impl Display for IntMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}{} {}",
+            if self.signed { 'i' } else { 'u' },
+            self.bits,
+            match self.overflow {
+                Overflow::Wrapping => "wrapping",
+                Overflow::Checked => "checked",
+            }
+        )
+    }
+}
@@ -0,0 +1,91 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Random numbers for rand, randint and randn, made with SplitMix64
+// Built in functions only get read access to the log, so the state is kept in a cell. It is shared
+// with every scoped copy of the log, so lambdas keep drawing new numbers instead of repeating them
+#[derive(Clone, Debug)]
+pub struct Random {
+    state: Rc<Cell<u64>>,
+}
+
+impl Default for Random {
+    // Seeded from the clock, so every session gives different numbers until /seed is used
+    fn default() -> Self {
+        Random::from_seed(clock_seed())
+    }
+}
+
+impl Random {
+    pub fn from_seed(seed: u64) -> Self {
+        Random {
+            state: Rc::new(Cell::new(seed)),
+        }
+    }
+
+    pub fn seed(&self, seed: u64) {
+        self.state.set(seed);
+    }
+
+    pub fn reseed_from_clock(&self) {
+        self.seed(clock_seed());
+    }
+
+    // Keeps the current state until the guard is dropped, then puts it back
+    // The live preview uses this so it shows what pressing enter will give rather than rerolling
+    pub fn freeze(&self) -> Frozen<'_> {
+        Frozen {
+            random: self,
+            state: self.state.get(),
+        }
+    }
+
+    pub fn next_u64(&self) -> u64 {
+        let state = self.state.get().wrapping_add(0x9e37_79b9_7f4a_7c15);
+        self.state.set(state);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // Uniform in [0, 1), using the top 53 bits so every value is equally likely
+    pub fn next_f64(&self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    // Uniform whole number from 0 to n - 1, redrawing the few values which would favour small numbers
+    pub fn below(&self, n: u64) -> u64 {
+        let zone = u64::MAX - u64::MAX % n;
+        loop {
+            let x = self.next_u64();
+            if x < zone {
+                return x % n;
+            }
+        }
+    }
+
+    // Standard normal by the Box-Muller transform, 1 - u avoids taking the log of 0
+    pub fn normal(&self) -> f64 {
+        let (u, v) = (1.0 - self.next_f64(), self.next_f64());
+        (-2.0 * u.ln()).sqrt() * (std::f64::consts::TAU * v).cos()
+    }
+}
+
+pub struct Frozen<'a> {
+    random: &'a Random,
+    state: u64,
+}
+
+impl Drop for Frozen<'_> {
+    fn drop(&mut self) {
+        self.random.seed(self.state);
+    }
+}
+
+fn clock_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64)
+}
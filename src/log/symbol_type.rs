@@ -1,7 +1,12 @@
-use crate::calculator::{num_types::NumType, CalculatorError};
+use crate::calculator::num_types::NumType;
+
+use super::{LazyNumFn, NumFn};
 
 pub enum SymbolType<'a> {
     Variable(&'a NumType),
-    DefaultFn(fn(Vec<NumType>) -> Result<NumType, CalculatorError>),
+    DefaultFn(NumFn),
+    // Built in functions given their parameters unevaluated
+    LazyFn(LazyNumFn),
+    #[allow(dead_code)] // TODO: Implement user functions
     UserFn,
 }
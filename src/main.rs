@@ -5,6 +5,7 @@ use config::{Config, Map, Value};
 use directories::ProjectDirs;
 use eframe::{run_native, App, CreationContext, NativeOptions};
 use egui::{CentralPanel, Color32, FontFamily, FontId, Id, Label, Layout, RichText, Style, TextEdit, TopBottomPanel};
+use calculator::number_format::{Notation, NumberFormat};
 use log::Log;
 
 mod calculator;
@@ -18,9 +19,11 @@ struct DeskCalc {
 }
 
 impl DeskCalc {
-    fn new(_cc: &CreationContext<'_>) -> Self {
+    fn new(_cc: &CreationContext<'_>, format: NumberFormat) -> Self {
+        let mut log = Log::new();
+        log.format = format;
         DeskCalc {
-            log: Log::new(),
+            log,
             ..Default::default()
         }
     }
@@ -61,9 +64,18 @@ impl App for DeskCalc {
                 self.out = calculator::calculate(&self.input_text, &self.log);
             }
 
-            ui.with_layout(Layout::right_to_left(egui::Align::Max), |ui| {
-                // Add output line
-                ui.add(Label::new(RichText::new(&self.out).heading().strong()));
+            ui.horizontal(|ui| {
+                // Show the current angle mode and integer type on the left
+                let mut modes = self.log.angle_mode.to_string();
+                if let Some(int_mode) = self.log.int_mode {
+                    modes += &format!(" {}", int_mode);
+                }
+                ui.add(Label::new(RichText::new(modes).weak()));
+
+                ui.with_layout(Layout::right_to_left(egui::Align::Max), |ui| {
+                    // Add output line
+                    ui.add(Label::new(RichText::new(&self.out).heading().strong()));
+                });
             });
         });
 
@@ -86,6 +98,33 @@ fn get_config_hex(map: &Map<String, Value>, key: &str) -> Option<Color32> {
     map.get(key).and_then(|s| s.clone().into_string().ok()).and_then(|s| Color32::from_hex(&s).ok())
 }
 
+// Reads the [format] table, anything missing or invalid keeps its default
+fn get_config_format(config: &Config) -> NumberFormat {
+    let mut format = NumberFormat::default();
+    if let Ok(map) = config.get_table("format") {
+        let digits = map.get("digits").and_then(|v| v.clone().into_uint().ok()).unwrap_or(6) as usize;
+        if let Some(notation) = map.get("notation").and_then(|v| v.clone().into_string().ok()) {
+            let notation = match notation.as_str() {
+                "fix" | "fixed" => Notation::Fixed(digits),
+                "sig" | "significant" => Notation::Significant(digits),
+                "sci" | "scientific" => Notation::Scientific(digits),
+                "eng" | "engineering" => Notation::Engineering(digits),
+                _ => Notation::Smart,
+            };
+            if notation.digits_in_range() {
+                format.notation = notation;
+            }
+        }
+        if let Some(group) = map.get("group_thousands").and_then(|v| v.clone().into_bool().ok()) {
+            format.group_thousands = group;
+        }
+        if let Some(separator) = map.get("separator").and_then(|v| v.clone().into_string().ok()).and_then(|s| s.chars().next()) {
+            format.separator = separator;
+        }
+    }
+    format
+}
+
 fn main() {
     let project_dirs = ProjectDirs::from("", "DrewCodesBadly", "DeskCalc");
     let config_builder = Config::builder();
@@ -100,6 +139,8 @@ fn main() {
         ..Default::default()
     };
 
+    let format = get_config_format(&config);
+
     // Visual styling according to config - only enabled if colors table has been declared
     let mut style = Style::default(); 
     if let Ok(map) = config.get_table("visuals") {
@@ -134,7 +175,7 @@ fn main() {
         win_option,
         Box::new(|cc| {
             cc.egui_ctx.set_style(style);
-            Ok(Box::new(DeskCalc::new(cc)))
+            Ok(Box::new(DeskCalc::new(cc, format)))
         }),
     )
     .expect("Failed to set up window");